@@ -0,0 +1,124 @@
+// Copyright (C) 2019-2021 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkvm::{dpc::Network, utilities::str::FromStr};
+
+use anyhow::{anyhow, Result};
+use std::{collections::BTreeMap, ops::Deref};
+
+/// The checkpoints of Testnet2 := \[(block_height, block_hash)\], in addition to the genesis block.
+/// Append a checkpoint only once its block is buried deeper than `ALEO_MAXIMUM_FORK_DEPTH` in the canon chain.
+///
+/// This table is intentionally empty until trusted block hashes of the Testnet2 canon chain are published.
+/// Until then, only the genesis block is enforced as a checkpoint.
+const TESTNET2_CHECKPOINTS: &[(u32, &str)] = &[];
+
+///
+/// A helper struct to represent the checkpoints of a network.
+///
+/// The current format of checkpoints is \[(block_height, block_hash)\], where each block hash is trusted
+/// to be in the canon chain. The genesis block is always a checkpoint.
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Checkpoints<N: Network> {
+    checkpoints: BTreeMap<u32, N::BlockHash>,
+}
+
+impl<N: Network> Checkpoints<N> {
+    /// Returns the checkpoints of the network.
+    pub fn load() -> Result<Self> {
+        match N::NETWORK_ID {
+            2 => Self::from_table(TESTNET2_CHECKPOINTS),
+            _ => Self::from_table(&[]),
+        }
+    }
+
+    /// Returns the checkpoints from the given table of block heights and block hashes, and the genesis block.
+    pub fn from_table(table: &[(u32, &str)]) -> Result<Self> {
+        let genesis_block = N::genesis_block();
+
+        let mut checkpoints = BTreeMap::new();
+        checkpoints.insert(genesis_block.height(), genesis_block.hash());
+
+        for (block_height, block_hash) in table {
+            let block_hash = N::BlockHash::from_str(block_hash).map_err(|_| anyhow!("Invalid checkpoint for block {}", block_height))?;
+            if let Some(previous_block_hash) = checkpoints.insert(*block_height, block_hash) {
+                if previous_block_hash != block_hash {
+                    return Err(anyhow!("Conflicting checkpoints for block {}", block_height));
+                }
+            }
+        }
+
+        Ok(Self { checkpoints })
+    }
+
+    /// Returns the block height of the latest checkpoint.
+    pub fn latest_block_height(&self) -> u32 {
+        self.checkpoints.keys().max().copied().unwrap_or(0)
+    }
+
+    /// Returns `true` if the given block hash does not contradict the checkpoint at the given block height.
+    pub fn is_consistent(&self, block_height: u32, block_hash: &N::BlockHash) -> bool {
+        match self.checkpoints.get(&block_height) {
+            Some(checkpoint) => checkpoint == block_hash,
+            None => true,
+        }
+    }
+}
+
+impl<N: Network> Deref for Checkpoints<N> {
+    type Target = BTreeMap<u32, N::BlockHash>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.checkpoints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::dpc::testnet2::Testnet2;
+
+    #[test]
+    fn test_checkpoints_genesis() {
+        let genesis_block = Testnet2::genesis_block();
+
+        let checkpoints = Checkpoints::<Testnet2>::load().unwrap();
+        assert_eq!(Some(&genesis_block.hash()), checkpoints.get(&0));
+        assert!(checkpoints.is_consistent(0, &genesis_block.hash()));
+        assert!(checkpoints.is_consistent(1, &genesis_block.hash()));
+        assert!(!checkpoints.is_consistent(0, &Default::default()));
+    }
+
+    #[test]
+    fn test_checkpoints_from_table() {
+        let genesis_block = Testnet2::genesis_block();
+        let block_hash = genesis_block.hash().to_string();
+
+        // A checkpoint at a new block height is appended.
+        let checkpoints = Checkpoints::<Testnet2>::from_table(&[(5, &block_hash)]).unwrap();
+        assert_eq!(2, checkpoints.len());
+        assert_eq!(5, checkpoints.latest_block_height());
+        assert!(checkpoints.is_consistent(5, &genesis_block.hash()));
+
+        // A checkpoint that contradicts the genesis block is rejected.
+        let other_block_hash = <Testnet2 as Network>::BlockHash::default().to_string();
+        assert!(Checkpoints::<Testnet2>::from_table(&[(0, &other_block_hash)]).is_err());
+
+        // A malformed checkpoint is rejected.
+        assert!(Checkpoints::<Testnet2>::from_table(&[(5, "ab1invalid")]).is_err());
+    }
+}
@@ -16,3 +16,6 @@
 
 pub mod block_locators;
 pub use block_locators::*;
+
+pub mod checkpoints;
+pub use checkpoints::*;
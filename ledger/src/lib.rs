@@ -18,9 +18,17 @@
 extern crate tracing;
 
 mod helpers;
-pub use helpers::BlockLocators;
+pub use helpers::{BlockLocators, Checkpoints};
 
 pub(crate) mod state;
-pub use state::{LedgerState, Metadata, MAXIMUM_BLOCK_LOCATORS, MAXIMUM_LINEAR_BLOCK_LOCATORS, MAXIMUM_QUADRATIC_BLOCK_LOCATORS};
+pub use state::{
+    block_work,
+    BlockTemplate,
+    LedgerState,
+    Metadata,
+    MAXIMUM_BLOCK_LOCATORS,
+    MAXIMUM_LINEAR_BLOCK_LOCATORS,
+    MAXIMUM_QUADRATIC_BLOCK_LOCATORS,
+};
 
 pub mod storage;
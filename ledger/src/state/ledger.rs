@@ -15,7 +15,7 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    helpers::BlockLocators,
+    helpers::{BlockLocators, Checkpoints},
     storage::{DataMap, Map, Storage},
 };
 use snarkvm::dpc::prelude::*;
@@ -25,9 +25,9 @@ use circular_queue::CircularQueue;
 use itertools::Itertools;
 use parking_lot::RwLock;
 use rand::{CryptoRng, Rng};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashSet},
     path::Path,
     sync::{
         atomic::{AtomicBool, AtomicU32, Ordering},
@@ -46,6 +46,12 @@ pub const MAXIMUM_QUADRATIC_BLOCK_LOCATORS: u32 = 64;
 /// The total maximum number of block locators.
 pub const MAXIMUM_BLOCK_LOCATORS: u32 = MAXIMUM_LINEAR_BLOCK_LOCATORS.saturating_add(MAXIMUM_QUADRATIC_BLOCK_LOCATORS);
 
+/// Returns the expected amount of work to mine a block with the given difficulty target,
+/// as a block hash meets the difficulty target with a probability of `(difficulty_target + 1) / 2^64`.
+pub fn block_work(difficulty_target: u64) -> u128 {
+    (u64::MAX as u128 + 1) / (difficulty_target as u128 + 1)
+}
+
 ///
 /// A helper struct containing transaction metadata.
 ///
@@ -72,7 +78,52 @@ impl<N: Network> Metadata<N> {
     }
 }
 
-#[derive(Clone, Debug)]
+///
+/// A helper struct containing the parameters for mining the next block on the ledger.
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlockTemplate<N: Network> {
+    previous_block_hash: N::BlockHash,
+    block_height: u32,
+    block_timestamp: i64,
+    difficulty_target: u64,
+    ledger_root: N::LedgerRoot,
+    block_reward: AleoAmount,
+}
+
+impl<N: Network> BlockTemplate<N> {
+    /// Returns the previous block hash.
+    pub fn previous_block_hash(&self) -> N::BlockHash {
+        self.previous_block_hash
+    }
+
+    /// Returns the block height.
+    pub fn block_height(&self) -> u32 {
+        self.block_height
+    }
+
+    /// Returns the block timestamp.
+    pub fn block_timestamp(&self) -> i64 {
+        self.block_timestamp
+    }
+
+    /// Returns the difficulty target.
+    pub fn difficulty_target(&self) -> u64 {
+        self.difficulty_target
+    }
+
+    /// Returns the ledger root.
+    pub fn ledger_root(&self) -> N::LedgerRoot {
+        self.ledger_root
+    }
+
+    /// Returns the block reward, for the coinbase transaction.
+    pub fn block_reward(&self) -> AleoAmount {
+        self.block_reward
+    }
+}
+
+#[derive(Debug)]
 pub struct LedgerState<N: Network> {
     /// The current ledger tree of block hashes.
     ledger_tree: Arc<RwLock<LedgerTree<N>>>,
@@ -88,10 +139,31 @@ pub struct LedgerState<N: Network> {
     ledger_roots: DataMap<N::LedgerRoot, u32>,
     /// The blocks of the ledger in storage.
     blocks: BlockState<N>,
+    /// The checkpoints that the canon chain must contain.
+    checkpoints: Checkpoints<N>,
     /// The indicator bit and tracker for a ledger in read-only mode.
     read_only: (bool, Arc<AtomicU32>),
 }
 
+/// A clone shares the storage of the ledger, with its own empty atomic batch,
+/// so that it never observes the writes of a batch in progress in the original ledger.
+impl<N: Network> Clone for LedgerState<N> {
+    fn clone(&self) -> Self {
+        let ledger_roots = self.ledger_roots.clone();
+        Self {
+            ledger_tree: self.ledger_tree.clone(),
+            latest_block: self.latest_block.clone(),
+            latest_block_hashes: self.latest_block_hashes.clone(),
+            latest_block_headers: self.latest_block_headers.clone(),
+            latest_block_locators: self.latest_block_locators.clone(),
+            blocks: self.blocks.clone_with_batch_of(&ledger_roots),
+            ledger_roots,
+            checkpoints: self.checkpoints.clone(),
+            read_only: self.read_only.clone(),
+        }
+    }
+}
+
 impl<N: Network> LedgerState<N> {
     /// Initializes a new instance of `LedgerState`.
     pub fn open<S: Storage, P: AsRef<Path>>(path: P, is_read_only: bool) -> Result<Self> {
@@ -110,6 +182,7 @@ impl<N: Network> LedgerState<N> {
             latest_block_locators: Default::default(),
             ledger_roots: storage.open_map("ledger_roots")?,
             blocks: BlockState::open(storage)?,
+            checkpoints: Checkpoints::load()?,
             read_only: (is_read_only, Arc::new(AtomicU32::new(genesis.height()))),
         };
 
@@ -125,8 +198,10 @@ impl<N: Network> LedgerState<N> {
 
         // If this is new storage, initialize it with the genesis block.
         if latest_block_height == 0u32 && !ledger.blocks.contains_block_height(0u32)? {
-            ledger.ledger_roots.insert(&genesis.previous_ledger_root(), &genesis.height())?;
-            ledger.blocks.add_block(genesis)?;
+            ledger.atomic_batch(|ledger| {
+                ledger.ledger_roots.insert(&genesis.previous_ledger_root(), &genesis.height())?;
+                ledger.blocks.add_block(genesis)
+            })?;
         }
 
         // Retrieve each block from genesis to validate state.
@@ -156,6 +231,19 @@ impl<N: Network> LedgerState<N> {
 
             // Add the block hash to the ledger tree.
             ledger.ledger_tree.write().add(&ledger.get_block_hash(block_height)?)?;
+
+            // Add the cumulative work of the block, if it is missing from storage of a previous version.
+            if !ledger.is_read_only() && !ledger.blocks.contains_cumulative_work(block_height)? {
+                let difficulty_target = ledger.get_block_header(block_height)?.difficulty_target();
+                ledger.blocks.add_cumulative_work(block_height, difficulty_target)?;
+            }
+        }
+
+        // Ensure the ledger contains the checkpoints up to the latest block height.
+        for (block_height, block_hash) in ledger.checkpoints.range(..=latest_block_height) {
+            if ledger.get_block_hash(*block_height)? != *block_hash {
+                return Err(anyhow!("Ledger contradicts the checkpoint at block {}", block_height));
+            }
         }
 
         // Update the latest ledger state.
@@ -218,11 +306,110 @@ impl<N: Network> LedgerState<N> {
         Ok(ledger)
     }
 
+    /// Repairs the ledger storage at the given path, and returns the reopened ledger.
+    ///
+    /// The ledger is reverted to its highest consistent block, which is the highest block that is fully
+    /// indexed in storage and extends the ledger tree of the blocks before it. The entries of every block
+    /// above it, including the dangling entries of a partially-written or partially-removed block,
+    /// are removed from storage. If the repair is interrupted, it is safe to repeat.
+    pub fn repair<S: Storage, P: AsRef<Path>>(path: P) -> Result<Self> {
+        {
+            // Open storage.
+            let storage = S::open(path.as_ref(), N::NETWORK_ID, false)?;
+            let ledger_roots: DataMap<N::LedgerRoot, u32> = storage.open_map("ledger_roots")?;
+            let blocks = BlockState::<N>::open(storage)?;
+
+            // Initialize the sets of entries that belong to the consistent blocks.
+            let mut consistent_ledger_roots = HashSet::new();
+            let mut consistent_block_hashes = HashSet::new();
+            let mut consistent_transaction_ids = HashSet::new();
+            let mut consistent_transition_ids = HashSet::new();
+            let mut corrected_cumulative_work = Vec::new();
+
+            // Determine the highest consistent block, by rebuilding the ledger tree from the genesis block.
+            let mut ledger_tree = LedgerTree::<N>::new()?;
+            let mut cumulative_work = 0u128;
+            let latest_block_height = blocks.block_heights.keys().max().unwrap_or(0);
+            for block_height in 0..=latest_block_height {
+                let block = match blocks.get_consistent_block(block_height) {
+                    Ok(block) => block,
+                    Err(error) => {
+                        warn!("Block {} is inconsistent in storage: {}", block_height, error);
+                        break;
+                    }
+                };
+
+                // Ensure the block extends the ledger tree, and its ledger root is stored with its block height.
+                let ledger_root = block.previous_ledger_root();
+                if ledger_root != ledger_tree.root() || ledger_roots.get(&ledger_root)? != Some(block_height) {
+                    warn!("Block {} has an incorrect ledger root in storage", block_height);
+                    break;
+                }
+                ledger_tree.add(&block.hash())?;
+
+                // Correct the cumulative work of the block, if it is incorrect in storage.
+                cumulative_work = cumulative_work.saturating_add(block_work(block.difficulty_target()));
+                if blocks.cumulative_work.get(&block_height)? != Some(cumulative_work) {
+                    corrected_cumulative_work.push((block_height, cumulative_work));
+                }
+
+                consistent_ledger_roots.insert(ledger_root);
+                consistent_block_hashes.insert(block.hash());
+                for transaction in block.transactions().iter() {
+                    consistent_transaction_ids.insert(transaction.transaction_id());
+                    consistent_transition_ids.extend(transaction.transition_ids());
+                }
+            }
+
+            // Correct the cumulative work, and remove the entries that do not belong to a consistent block,
+            // in one atomic batch, so that an interrupted repair leaves the ledger storage unchanged.
+            let number_of_blocks = consistent_block_hashes.len() as u32;
+            let transactions = &blocks.transactions;
+            let number_of_entries = atomic_map_batch(&ledger_roots, || {
+                for (block_height, cumulative_work) in &corrected_cumulative_work {
+                    blocks.cumulative_work.insert(block_height, cumulative_work)?;
+                }
+
+                Ok([
+                    remove_entries(&ledger_roots, |ledger_root, _| !consistent_ledger_roots.contains(ledger_root))?,
+                    remove_entries(&blocks.block_heights, |block_height, _| *block_height >= number_of_blocks)?,
+                    remove_entries(&blocks.cumulative_work, |block_height, _| *block_height >= number_of_blocks)?,
+                    remove_entries(&blocks.block_headers, |hash, _| !consistent_block_hashes.contains(hash))?,
+                    remove_entries(&blocks.block_transactions, |hash, _| !consistent_block_hashes.contains(hash))?,
+                    remove_entries(&transactions.transactions, |id, _| !consistent_transaction_ids.contains(id))?,
+                    remove_entries(&transactions.events, |id, _| !consistent_transaction_ids.contains(id))?,
+                    remove_entries(&transactions.transitions, |id, _| !consistent_transition_ids.contains(id))?,
+                    remove_entries(&transactions.serial_numbers, |_, id| !consistent_transition_ids.contains(id))?,
+                    remove_entries(&transactions.commitments, |_, id| !consistent_transition_ids.contains(id))?,
+                    remove_entries(&transactions.ciphertext_ids, |_, id| !consistent_transition_ids.contains(id))?,
+                ]
+                .iter()
+                .sum::<usize>())
+            })?;
+
+            info!("Removed {} inconsistent entries from the ledger storage", number_of_entries);
+        }
+
+        // Reopen the ledger, which rebuilds the ledger tree, and reinitializes storage with the genesis block if it was inconsistent.
+        Self::open::<S, _>(path, false)
+    }
+
     /// Returns `true` if the ledger is in read-only mode.
     pub fn is_read_only(&self) -> bool {
         self.read_only.0
     }
 
+    /// Returns the checkpoints that the canon chain must contain.
+    pub fn checkpoints(&self) -> &Checkpoints<N> {
+        &self.checkpoints
+    }
+
+    /// Flushes the ledger state to persistent storage.
+    pub fn flush(&self) -> Result<()> {
+        // The maps of the ledger share the same underlying storage, so flushing one map flushes all of them.
+        self.ledger_roots.flush()
+    }
+
     /// Returns the latest block.
     pub fn latest_block(&self) -> Block<N> {
         self.latest_block.read().clone()
@@ -263,6 +450,16 @@ impl<N: Network> LedgerState<N> {
         self.latest_block_locators.read().clone()
     }
 
+    /// Returns the cumulative work of the canon chain, up to the latest block.
+    pub fn latest_cumulative_work(&self) -> Result<u128> {
+        self.blocks.get_cumulative_work(self.latest_block_height())
+    }
+
+    /// Returns the cumulative work of the canon chain, up to the given block height.
+    pub fn get_cumulative_work(&self, block_height: u32) -> Result<u128> {
+        self.blocks.get_cumulative_work(block_height)
+    }
+
     /// Returns the latest ledger root.
     pub fn latest_ledger_root(&self) -> N::LedgerRoot {
         self.ledger_tree.read().root()
@@ -497,14 +694,104 @@ impl<N: Network> LedgerState<N> {
         Ok(true)
     }
 
-    /// Mines a new block using the latest state of the given ledger.
-    pub fn mine_next_block<R: Rng + CryptoRng>(
+    /// Checks that the given block headers form a valid chain extending the block at the given block height,
+    /// and returns the cumulative work of the chain up to the last block header.
+    ///
+    /// The block headers are given with their block hashes, in ascending order of block height. As a block header
+    /// does not commit to its block hash, the block hashes are only checked once the corresponding blocks are added.
+    pub fn check_block_headers(&self, block_height: u32, block_headers: &[(N::BlockHash, BlockHeader<N>)]) -> Result<u128> {
+        // Retrieve the block header that the given block headers extend.
+        let previous_block_header = self.get_block_header(block_height)?;
+        let cumulative_work = self.blocks.get_cumulative_work(block_height)?;
+        self.check_block_headers_after(&previous_block_header, cumulative_work, block_headers)
+    }
+
+    /// Checks that the given block headers form a valid chain extending the given block header, with the given
+    /// cumulative work, and returns the cumulative work of the chain up to the last block header.
+    ///
+    /// The given block header may be the last block header of a fork, which allows a fork to be checked in parts.
+    pub fn check_block_headers_after(
         &self,
-        recipient: Address<N>,
-        transactions: &[Transaction<N>],
-        terminator: &AtomicBool,
-        rng: &mut R,
-    ) -> Result<Block<N>> {
+        previous_block_header: &BlockHeader<N>,
+        cumulative_work: u128,
+        block_headers: &[(N::BlockHash, BlockHeader<N>)],
+    ) -> Result<u128> {
+        // Ensure the list of block headers is not empty.
+        if block_headers.is_empty() {
+            return Err(anyhow!("The list of block headers is empty"));
+        }
+
+        let mut previous_block_header = previous_block_header.clone();
+        let mut cumulative_work = cumulative_work;
+        let mut block_hashes = HashSet::with_capacity(block_headers.len());
+        let now = chrono::Utc::now().timestamp();
+
+        for (block_hash, block_header) in block_headers {
+            // Ensure the block height increments by one.
+            let expected_block_height = previous_block_header.height() + 1;
+            if block_header.height() != expected_block_height {
+                return Err(anyhow!(
+                    "Block header {} should have block height {}",
+                    block_header.height(),
+                    expected_block_height
+                ));
+            }
+
+            // Ensure the block hash is unique.
+            if !block_hashes.insert(*block_hash) {
+                return Err(anyhow!("Block header {} has a repeat block hash", block_header.height()));
+            }
+
+            // Ensure the block hash matches the checkpoint, if one exists at this block height.
+            if !self.checkpoints.is_consistent(block_header.height(), block_hash) {
+                return Err(anyhow!("Block header {} contradicts the checkpoint", block_header.height()));
+            }
+
+            // Ensure the block header is valid, including its proof of work.
+            if !block_header.is_valid() {
+                return Err(anyhow!("Block header {} is invalid", block_header.height()));
+            }
+
+            // Ensure the block timestamp is within the declared time limit.
+            if block_header.timestamp() > (now + TWO_HOURS_UNIX) {
+                return Err(anyhow!(
+                    "Block header {} has a timestamp that exceeds the time limit",
+                    block_header.height()
+                ));
+            }
+
+            // Ensure the block timestamp is after the previous block timestamp.
+            if block_header.timestamp() <= previous_block_header.timestamp() {
+                return Err(anyhow!(
+                    "Block header {} has a timestamp before its previous block",
+                    block_header.height()
+                ));
+            }
+
+            // Ensure the expected difficulty target is met.
+            let expected_difficulty_target = Blocks::<N>::compute_difficulty_target(
+                previous_block_header.timestamp(),
+                previous_block_header.difficulty_target(),
+                block_header.timestamp(),
+            );
+            if block_header.difficulty_target() != expected_difficulty_target {
+                return Err(anyhow!(
+                    "Block header {} has an incorrect difficulty target. Found {}, but expected {}",
+                    block_header.height(),
+                    block_header.difficulty_target(),
+                    expected_difficulty_target
+                ));
+            }
+
+            cumulative_work = cumulative_work.saturating_add(block_work(block_header.difficulty_target()));
+            previous_block_header = block_header.clone();
+        }
+
+        Ok(cumulative_work)
+    }
+
+    /// Returns the parameters for mining the next block, using the latest state of the ledger.
+    pub fn get_block_template(&self) -> BlockTemplate<N> {
         // Prepare the new block.
         let previous_block_hash = self.latest_block_hash();
         let block_height = self.latest_block_height() + 1;
@@ -515,23 +802,40 @@ impl<N: Network> LedgerState<N> {
         let block_timestamp = chrono::Utc::now().timestamp();
         let difficulty_target = Blocks::<N>::compute_difficulty_target(previous_timestamp, previous_difficulty_target, block_timestamp);
 
-        // Construct the ledger root.
-        let ledger_root = self.latest_ledger_root();
+        BlockTemplate {
+            previous_block_hash,
+            block_height,
+            block_timestamp,
+            difficulty_target,
+            ledger_root: self.latest_ledger_root(),
+            block_reward: Block::<N>::block_reward(block_height),
+        }
+    }
+
+    /// Mines a new block using the latest state of the given ledger.
+    pub fn mine_next_block<R: Rng + CryptoRng>(
+        &self,
+        recipient: Address<N>,
+        transactions: &[Transaction<N>],
+        terminator: &AtomicBool,
+        rng: &mut R,
+    ) -> Result<Block<N>> {
+        // Prepare the new block.
+        let template = self.get_block_template();
 
         // Craft a coinbase transaction.
-        let amount = Block::<N>::block_reward(block_height);
-        let coinbase_transaction = Transaction::<N>::new_coinbase(recipient, amount, rng)?;
+        let coinbase_transaction = Transaction::<N>::new_coinbase(recipient, template.block_reward(), rng)?;
 
         // Construct the new block transactions.
         let transactions = Transactions::from(&[&[coinbase_transaction], transactions].concat())?;
 
         // Mine the next block.
         match Block::mine(
-            previous_block_hash,
-            block_height,
-            block_timestamp,
-            difficulty_target,
-            ledger_root,
+            template.previous_block_hash(),
+            template.block_height(),
+            template.block_timestamp(),
+            template.difficulty_target(),
+            template.ledger_root(),
             transactions,
             terminator,
             rng,
@@ -548,6 +852,89 @@ impl<N: Network> LedgerState<N> {
             return Err(anyhow!("Ledger is in read-only mode"));
         }
 
+        // Ensure the block is valid as the next block in the ledger.
+        self.check_next_block(block)?;
+
+        // Add the block to storage atomically, so that a failure does not leave a partially-written block.
+        self.atomic_batch(|ledger| ledger.insert_next_block(block))?;
+        self.update_latest_block(block)
+    }
+
+    /// Switches the canon chain to the given branch of blocks, which extends the block at the given block height,
+    /// returning the removed blocks of the previous canon chain on success.
+    ///
+    /// The branch must have a larger cumulative work than the canon chain. The revert and the blocks of the branch
+    /// are written to storage in one atomic batch, once every block in the branch is valid. If any block in the branch
+    /// is invalid, the previous canon chain is left unchanged, and the error is returned.
+    pub fn switch_to_branch(&mut self, block_height: u32, blocks: &[Block<N>]) -> Result<Vec<Block<N>>> {
+        // If the storage is in read-only mode, this method cannot be called.
+        if self.is_read_only() {
+            return Err(anyhow!("Ledger is in read-only mode"));
+        }
+
+        // Ensure the branch has a larger cumulative work than the canon chain.
+        // The block headers of the branch are verified with the blocks themselves, as they are added.
+        let branch_cumulative_work = blocks
+            .iter()
+            .fold(self.get_cumulative_work(block_height)?, |cumulative_work, block| {
+                cumulative_work.saturating_add(block_work(block.difficulty_target()))
+            });
+        if branch_cumulative_work <= self.latest_cumulative_work()? {
+            return Err(anyhow!(
+                "The branch at block {} does not have more cumulative work than the canon chain",
+                block_height
+            ));
+        }
+
+        // Revert the canon chain to the block that the branch extends, and add the blocks of the branch, in one atomic batch.
+        let latest_block_height = self.latest_block_height();
+        let result = self.atomic_batch(|ledger| {
+            let removed_blocks = match block_height < latest_block_height {
+                true => {
+                    let removed_blocks = ledger.remove_blocks(block_height)?;
+                    ledger.reset_latest_block(block_height)?;
+                    removed_blocks
+                }
+                false => vec![],
+            };
+
+            for block in blocks {
+                ledger.check_next_block(block)?;
+                ledger.insert_next_block(block)?;
+                ledger.update_latest_block(block)?;
+            }
+
+            Ok(removed_blocks)
+        });
+
+        // On failure, nothing was written to storage, so restore the latest ledger state from the canon chain.
+        if result.is_err() {
+            self.reset_latest_block(latest_block_height)?;
+        }
+        result
+    }
+
+    /// Reverts the ledger state back to the given block height, returning the removed blocks on success.
+    /// The blocks are removed from storage atomically.
+    pub fn revert_to_block_height(&mut self, block_height: u32) -> Result<Vec<Block<N>>> {
+        // If the storage is in read-only mode, this method cannot be called.
+        if self.is_read_only() {
+            return Err(anyhow!("Ledger is in read-only mode"));
+        }
+
+        // Remove the blocks from storage atomically, so that a failure leaves the ledger at its latest block.
+        let blocks = self.atomic_batch(|ledger| ledger.remove_blocks(block_height))?;
+        self.reset_latest_block(block_height)?;
+        Ok(blocks)
+    }
+
+    /// Checks that the given block is valid as the next block in the ledger.
+    fn check_next_block(&self, block: &Block<N>) -> Result<()> {
+        // Ensure the block hash matches the checkpoint, if one exists at this block height.
+        if !self.checkpoints.is_consistent(block.height(), &block.hash()) {
+            return Err(anyhow!("Block {} contradicts the checkpoint at this block height", block.height()));
+        }
+
         // Ensure the block itself is valid.
         if !block.is_valid() {
             return Err(anyhow!("Block {} is invalid", block.height()));
@@ -650,9 +1037,18 @@ impl<N: Network> LedgerState<N> {
             }
         }
 
+        Ok(())
+    }
+
+    /// Writes the given block to storage, as the next block in the ledger.
+    fn insert_next_block(&self, block: &Block<N>) -> Result<()> {
         self.blocks.add_block(block)?;
+        self.ledger_roots.insert(&block.previous_ledger_root(), &block.height())
+    }
+
+    /// Updates the latest ledger state with the given block, once it is written as the next block in the ledger.
+    fn update_latest_block(&mut self, block: &Block<N>) -> Result<()> {
         self.ledger_tree.write().add(&block.hash())?;
-        self.ledger_roots.insert(&block.previous_ledger_root(), &block.height())?;
         self.latest_block_hashes.write().push(block.hash());
         self.latest_block_headers.write().push(block.header().clone());
         *self.latest_block_locators.write() = self.get_block_locators(block.height())?;
@@ -660,13 +1056,9 @@ impl<N: Network> LedgerState<N> {
         Ok(())
     }
 
-    /// Reverts the ledger state back to the given block height, returning the removed blocks on success.
-    pub fn revert_to_block_height(&mut self, block_height: u32) -> Result<Vec<Block<N>>> {
-        // If the storage is in read-only mode, this method cannot be called.
-        if self.is_read_only() {
-            return Err(anyhow!("Ledger is in read-only mode"));
-        }
-
+    /// Removes the blocks after the given block height from storage, returning the removed blocks in increasing order.
+    /// The ledger tree and the latest ledger state are not updated.
+    fn remove_blocks(&self, block_height: u32) -> Result<Vec<Block<N>>> {
         // Determine the number of blocks to remove.
         let latest_block_height = self.latest_block_height();
         let number_of_blocks = latest_block_height.saturating_sub(block_height);
@@ -678,36 +1070,36 @@ impl<N: Network> LedgerState<N> {
 
         // Initialize a list of the removed blocks.
         let mut blocks = Vec::with_capacity(number_of_blocks as usize);
-        {
-            // Acquire the write lock on the latest block.
-            let mut latest_block = self.latest_block.write();
 
-            while latest_block.height() > block_height {
-                // Update the internal state of the ledger, except for the ledger tree.
-                self.blocks.remove_block(latest_block.height())?;
-                self.ledger_roots.remove(&latest_block.previous_ledger_root())?;
+        for height in (block_height + 1..=latest_block_height).rev() {
+            let block = self.get_block(height)?;
 
-                // Append this block to the final output.
-                blocks.push(latest_block.clone());
+            // Update the internal state of the ledger, except for the ledger tree.
+            self.blocks.remove_block(height)?;
+            self.ledger_roots.remove(&block.previous_ledger_root())?;
 
-                *latest_block = match latest_block.height() == 0 {
-                    true => N::genesis_block().clone(),
-                    false => self.get_block(latest_block.height().saturating_sub(1))?,
-                };
-            }
+            // Append this block to the final output.
+            blocks.push(block);
         }
 
-        // Regenerate the latest ledger state.
-        self.regenerate_latest_ledger_state()?;
-        // Regenerate the ledger tree.
-        self.regenerate_ledger_tree()?;
-
         // Reverse the order of the blocks, so they are in increasing order (i.e. 1, 2, 3...).
         blocks.reverse();
         // Return the removed blocks.
         Ok(blocks)
     }
 
+    /// Sets the latest block to the block at the given block height in storage,
+    /// and regenerates the latest ledger state and the ledger tree.
+    fn reset_latest_block(&mut self, block_height: u32) -> Result<()> {
+        // Update the latest block.
+        *self.latest_block.write() = self.get_block(block_height)?;
+
+        // Regenerate the latest ledger state.
+        self.regenerate_latest_ledger_state()?;
+        // Regenerate the ledger tree.
+        self.regenerate_ledger_tree()
+    }
+
     ///
     /// Returns a ledger proof for the given commitment.
     ///
@@ -835,11 +1227,28 @@ impl<N: Network> LedgerState<N> {
 
         Ok(())
     }
+
+    /// Performs the given writes to storage in an atomic batch, which is written on success and discarded on failure.
+    /// Reads within the batch observe its buffered writes.
+    fn atomic_batch<T>(&mut self, writes: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.ledger_roots.start_batch()?;
+        match writes(self) {
+            Ok(output) => {
+                self.ledger_roots.finish_batch()?;
+                Ok(output)
+            }
+            Err(error) => {
+                self.ledger_roots.abort_batch();
+                Err(error)
+            }
+        }
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 struct BlockState<N: Network> {
     block_heights: DataMap<u32, N::BlockHash>,
+    cumulative_work: DataMap<u32, u128>,
     block_headers: DataMap<N::BlockHash, BlockHeader<N>>,
     block_transactions: DataMap<N::BlockHash, Vec<N::TransactionID>>,
     transactions: TransactionState<N>,
@@ -850,12 +1259,24 @@ impl<N: Network> BlockState<N> {
     fn open<S: Storage>(storage: S) -> Result<Self> {
         Ok(Self {
             block_heights: storage.open_map("block_heights")?,
+            cumulative_work: storage.open_map("cumulative_work")?,
             block_headers: storage.open_map("block_headers")?,
             block_transactions: storage.open_map("block_transactions")?,
             transactions: TransactionState::open(storage)?,
         })
     }
 
+    /// Returns a clone of the block state, whose maps share the atomic batch of the given map.
+    fn clone_with_batch_of<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned>(&self, map: &DataMap<K, V>) -> Self {
+        Self {
+            block_heights: self.block_heights.clone_with_batch_of(map),
+            cumulative_work: self.cumulative_work.clone_with_batch_of(map),
+            block_headers: self.block_headers.clone_with_batch_of(map),
+            block_transactions: self.block_transactions.clone_with_batch_of(map),
+            transactions: self.transactions.clone_with_batch_of(map),
+        }
+    }
+
     /// Returns `true` if the given block height exists in storage.
     fn contains_block_height(&self, block_height: u32) -> Result<bool> {
         self.block_heights.contains_key(&block_height)
@@ -866,6 +1287,11 @@ impl<N: Network> BlockState<N> {
         self.block_headers.contains_key(block_hash)
     }
 
+    /// Returns `true` if the cumulative work of the given block height exists in storage.
+    fn contains_cumulative_work(&self, block_height: u32) -> Result<bool> {
+        self.cumulative_work.contains_key(&block_height)
+    }
+
     /// Returns `true` if the given transaction ID exists in storage.
     fn contains_transaction(&self, transaction_id: &N::TransactionID) -> Result<bool> {
         self.transactions.contains_transaction(transaction_id)
@@ -932,6 +1358,14 @@ impl<N: Network> BlockState<N> {
             .collect()
     }
 
+    /// Returns the cumulative work of the canon chain, up to the given block height.
+    fn get_cumulative_work(&self, block_height: u32) -> Result<u128> {
+        match self.cumulative_work.get(&block_height)? {
+            Some(cumulative_work) => Ok(cumulative_work),
+            None => return Err(anyhow!("Block {} missing from cumulative work map", block_height)),
+        }
+    }
+
     /// Returns the previous block hash for the given block height.
     fn get_previous_block_hash(&self, block_height: u32) -> Result<N::BlockHash> {
         match block_height == 0 {
@@ -1002,6 +1436,23 @@ impl<N: Network> BlockState<N> {
         Ok(Block::from(previous_block_hash, block_header, transactions)?)
     }
 
+    /// Returns the block for the given block height, if it is fully indexed in storage.
+    fn get_consistent_block(&self, block_height: u32) -> Result<Block<N>> {
+        // Ensure the block is stored with its own block height and block hash.
+        let block = self.get_block(block_height)?;
+        if block.height() != block_height || block.hash() != self.get_block_hash(block_height)? {
+            return Err(anyhow!("Block {} is stored with an incorrect block hash", block_height));
+        }
+
+        // Ensure the transactions of the block are fully indexed in storage.
+        for (index, transaction) in block.transactions().iter().enumerate() {
+            let metadata = Metadata::<N>::new(block_height, block.hash(), block.timestamp(), index as u16);
+            self.transactions.check_transaction(transaction, metadata)?;
+        }
+
+        Ok(block)
+    }
+
     /// Returns the blocks from the given `start_block_height` to `end_block_height` (inclusive).
     fn get_blocks(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<Block<N>>> {
         // Ensure the starting block height is less than the ending block height.
@@ -1037,6 +1488,8 @@ impl<N: Network> BlockState<N> {
 
             // Insert the block height.
             self.block_heights.insert(&block_height, &block_hash)?;
+            // Insert the cumulative work.
+            self.add_cumulative_work(block_height, block.difficulty_target())?;
             // Insert the block header.
             self.block_headers.insert(&block_hash, block_header)?;
             // Insert the block transactions.
@@ -1051,6 +1504,16 @@ impl<N: Network> BlockState<N> {
         }
     }
 
+    /// Adds the cumulative work of the given block height to storage, extending the cumulative work of the previous block.
+    fn add_cumulative_work(&self, block_height: u32, difficulty_target: u64) -> Result<()> {
+        let previous_cumulative_work = match block_height == 0 {
+            true => 0,
+            false => self.get_cumulative_work(block_height - 1)?,
+        };
+        let cumulative_work = previous_cumulative_work.saturating_add(block_work(difficulty_target));
+        self.cumulative_work.insert(&block_height, &cumulative_work)
+    }
+
     /// Removes the given block height from storage.
     fn remove_block(&self, block_height: u32) -> Result<()> {
         // Ensure the block height is not the genesis block.
@@ -1085,6 +1548,8 @@ impl<N: Network> BlockState<N> {
 
             // Remove the block height.
             self.block_heights.remove(&block_height)?;
+            // Remove the cumulative work.
+            self.cumulative_work.remove(&block_height)?;
             // Remove the block header.
             self.block_headers.remove(&block_hash)?;
             // Remove the block transactions.
@@ -1099,7 +1564,7 @@ impl<N: Network> BlockState<N> {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 #[allow(clippy::type_complexity)]
 struct TransactionState<N: Network> {
     transactions: DataMap<N::TransactionID, (N::LedgerRoot, Vec<N::TransitionID>, Metadata<N>)>,
@@ -1123,6 +1588,18 @@ impl<N: Network> TransactionState<N> {
         })
     }
 
+    /// Returns a clone of the transaction state, whose maps share the atomic batch of the given map.
+    fn clone_with_batch_of<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned>(&self, map: &DataMap<K, V>) -> Self {
+        Self {
+            transactions: self.transactions.clone_with_batch_of(map),
+            transitions: self.transitions.clone_with_batch_of(map),
+            serial_numbers: self.serial_numbers.clone_with_batch_of(map),
+            commitments: self.commitments.clone_with_batch_of(map),
+            ciphertext_ids: self.ciphertext_ids.clone_with_batch_of(map),
+            events: self.events.clone_with_batch_of(map),
+        }
+    }
+
     /// Returns `true` if the given transaction ID exists in storage.
     fn contains_transaction(&self, transaction_id: &N::TransactionID) -> Result<bool> {
         self.transactions.contains_key(transaction_id)
@@ -1204,6 +1681,51 @@ impl<N: Network> TransactionState<N> {
         }
     }
 
+    /// Ensures the given transaction is fully indexed in storage, with the given metadata.
+    fn check_transaction(&self, transaction: &Transaction<N>, metadata: Metadata<N>) -> Result<()> {
+        let transaction_id = transaction.transaction_id();
+
+        // Ensure the transaction and its events are stored with the given metadata.
+        if self.get_transaction_metadata(&transaction_id)? != metadata {
+            return Err(anyhow!("Transaction {} has incorrect metadata in storage", transaction_id));
+        }
+        if !self.events.contains_key(&transaction_id)? {
+            return Err(anyhow!("Transaction {} missing from events map", transaction_id));
+        }
+
+        for (i, transition) in transaction.transitions().iter().enumerate() {
+            let transition_id = transition.transition_id();
+
+            // Ensure the transition is stored with the transaction.
+            let is_stored = match self.transitions.get(&transition_id)? {
+                Some((candidate_transaction_id, index, _)) => candidate_transaction_id == transaction_id && index == i as u8,
+                None => false,
+            };
+            if !is_stored {
+                return Err(anyhow!("Transition {} missing from transitions map", transition_id));
+            }
+
+            // Ensure the serial numbers, commitments, and ciphertext IDs are stored with the transition.
+            for serial_number in transition.serial_numbers() {
+                if self.serial_numbers.get(serial_number)? != Some(transition_id) {
+                    return Err(anyhow!("Serial number {} missing from serial numbers map", serial_number));
+                }
+            }
+            for commitment in transition.commitments() {
+                if self.commitments.get(commitment)? != Some(transition_id) {
+                    return Err(anyhow!("Commitment {} missing from commitments map", commitment));
+                }
+            }
+            for ciphertext_id in transition.to_ciphertext_ids() {
+                if self.ciphertext_ids.get(&*ciphertext_id?)? != Some(transition_id) {
+                    return Err(anyhow!("Transition {} missing from ciphertext IDs map", transition_id));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Adds the given transaction to storage.
     fn add_transaction(&self, transaction: &Transaction<N>, metadata: Metadata<N>) -> Result<()> {
         // Ensure the transaction does not exist.
@@ -1293,3 +1815,38 @@ impl<N: Network> TransactionState<N> {
         }
     }
 }
+
+/// Performs the given writes to the storage of the given map in an atomic batch,
+/// which is written on success and discarded on failure.
+fn atomic_map_batch<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned, T>(
+    map: &DataMap<K, V>,
+    writes: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    map.start_batch()?;
+    match writes() {
+        Ok(output) => {
+            map.finish_batch()?;
+            Ok(output)
+        }
+        Err(error) => {
+            map.abort_batch();
+            Err(error)
+        }
+    }
+}
+
+/// Removes the entries of the given map that match the given predicate, returning the number of removed entries.
+fn remove_entries<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned>(
+    map: &DataMap<K, V>,
+    predicate: impl Fn(&K, &V) -> bool,
+) -> Result<usize> {
+    let keys = map
+        .iter()
+        .filter(|(key, value)| predicate(key, value))
+        .map(|(key, _)| key)
+        .collect::<Vec<_>>();
+    for key in keys.iter() {
+        map.remove(key)?;
+    }
+    Ok(keys.len())
+}
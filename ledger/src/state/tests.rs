@@ -15,7 +15,8 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    storage::{rocksdb::RocksDB, Storage},
+    block_work,
+    storage::{rocksdb::RocksDB, Map, Storage},
     LedgerState,
 };
 use snarkvm::dpc::{prelude::*, testnet2::Testnet2};
@@ -55,6 +56,23 @@ fn test_genesis() {
     assert_eq!(ledger_tree.root(), ledger.latest_ledger_root());
 }
 
+#[test]
+fn test_get_block_template() {
+    // Initialize a new ledger.
+    let ledger = new_ledger::<Testnet2, RocksDB>();
+
+    // Retrieve the genesis block.
+    let genesis = Testnet2::genesis_block();
+
+    // Ensure the block template extends the genesis block.
+    let template = ledger.get_block_template();
+    assert_eq!(genesis.hash(), template.previous_block_hash());
+    assert_eq!(1, template.block_height());
+    assert!(template.block_timestamp() >= genesis.timestamp());
+    assert_eq!(ledger.latest_ledger_root(), template.ledger_root());
+    assert_eq!(Block::<Testnet2>::block_reward(1), template.block_reward());
+}
+
 #[test]
 fn test_add_next_block() {
     let rng = &mut thread_rng();
@@ -101,6 +119,168 @@ fn test_add_next_block() {
     assert_eq!(Some(&(genesis.hash(), None)), block_locators.get(&genesis.height()));
 }
 
+#[test]
+fn test_check_block_headers() {
+    let rng = &mut thread_rng();
+    let terminator = AtomicBool::new(false);
+
+    // Initialize a new ledger.
+    let mut ledger = new_ledger::<Testnet2, RocksDB>();
+
+    // Initialize a new account.
+    let account = Account::<Testnet2>::new(&mut thread_rng());
+    let address = account.address();
+
+    // Mine the next block, without adding it to the ledger.
+    let block = ledger.mine_next_block(address, &[], &terminator, rng).expect("Failed to mine");
+    let block_headers = vec![(block.hash(), block.header().clone())];
+
+    // Ensure the block header extends the genesis block.
+    let cumulative_work = ledger
+        .check_block_headers(0, &block_headers)
+        .expect("Failed to check block headers");
+    // Ensure the block header is checked the same way against the genesis block header.
+    let genesis = Testnet2::genesis_block();
+    let genesis_work = ledger.get_cumulative_work(0).expect("Failed to get cumulative work");
+    assert_eq!(
+        cumulative_work,
+        ledger
+            .check_block_headers_after(genesis.header(), genesis_work, &block_headers)
+            .expect("Failed to check block headers")
+    );
+    // Ensure an empty list of block headers is rejected.
+    assert!(ledger.check_block_headers(0, &[]).is_err());
+    // Ensure a repeated block header is rejected.
+    let repeated_block_headers = vec![block_headers[0].clone(), block_headers[0].clone()];
+    assert!(ledger.check_block_headers(0, &repeated_block_headers).is_err());
+
+    // Ensure the block header does not extend itself.
+    ledger.add_next_block(&block).expect("Failed to add next block to ledger");
+    assert!(ledger.check_block_headers(1, &block_headers).is_err());
+}
+
+#[test]
+fn test_cumulative_work() {
+    let rng = &mut thread_rng();
+    let terminator = AtomicBool::new(false);
+
+    // Initialize a new ledger.
+    let mut ledger = new_ledger::<Testnet2, RocksDB>();
+
+    // Ensure the cumulative work starts from the work of the genesis block.
+    let genesis_work = block_work(Testnet2::genesis_block().difficulty_target());
+    assert_eq!(genesis_work, ledger.latest_cumulative_work().unwrap());
+
+    // Initialize a new account.
+    let account = Account::<Testnet2>::new(&mut thread_rng());
+    let address = account.address();
+
+    // Mine the next block.
+    let block = ledger.mine_next_block(address, &[], &terminator, rng).expect("Failed to mine");
+    let expected_cumulative_work = genesis_work + block_work(block.difficulty_target());
+
+    // Ensure the cumulative work of the block header extends the genesis block.
+    let block_headers = vec![(block.hash(), block.header().clone())];
+    assert_eq!(expected_cumulative_work, ledger.check_block_headers(0, &block_headers).unwrap());
+
+    // Ensure the cumulative work is updated with the next block.
+    ledger.add_next_block(&block).expect("Failed to add next block to ledger");
+    assert_eq!(expected_cumulative_work, ledger.latest_cumulative_work().unwrap());
+
+    // Ensure the cumulative work is reverted with the block.
+    ledger.revert_to_block_height(0).expect("Failed to revert the ledger");
+    assert_eq!(genesis_work, ledger.latest_cumulative_work().unwrap());
+}
+
+#[test]
+fn test_switch_to_branch() {
+    let rng = &mut thread_rng();
+    let terminator = AtomicBool::new(false);
+
+    // Initialize two new ledgers.
+    let mut ledger = new_ledger::<Testnet2, RocksDB>();
+    let mut other_ledger = new_ledger::<Testnet2, RocksDB>();
+
+    // Initialize a new account.
+    let account = Account::<Testnet2>::new(&mut thread_rng());
+    let address = account.address();
+
+    // Mine one block on the first ledger, and two blocks on the second ledger.
+    let block = ledger.mine_next_block(address, &[], &terminator, rng).expect("Failed to mine");
+    ledger.add_next_block(&block).expect("Failed to add next block to ledger");
+    let mut branch = Vec::with_capacity(2);
+    for _ in 0..2 {
+        let other_block = other_ledger
+            .mine_next_block(address, &[], &terminator, rng)
+            .expect("Failed to mine");
+        other_ledger
+            .add_next_block(&other_block)
+            .expect("Failed to add next block to ledger");
+        branch.push(other_block);
+    }
+
+    // Ensure a branch without more cumulative work is rejected, and the canon chain is unchanged.
+    assert!(ledger.switch_to_branch(0, &[block.clone()]).is_err());
+    assert_eq!(block.hash(), ledger.latest_block_hash());
+
+    // Ensure the ledger switches to the branch with more cumulative work.
+    let removed_blocks = ledger.switch_to_branch(0, &branch).expect("Failed to switch to the branch");
+    assert_eq!(vec![block], removed_blocks);
+    assert_eq!(2, ledger.latest_block_height());
+    assert_eq!(other_ledger.latest_block_hash(), ledger.latest_block_hash());
+    assert_eq!(
+        other_ledger.latest_cumulative_work().unwrap(),
+        ledger.latest_cumulative_work().unwrap()
+    );
+}
+
+#[test]
+fn test_switch_to_invalid_branch() {
+    let rng = &mut thread_rng();
+    let terminator = AtomicBool::new(false);
+
+    // Initialize two new ledgers.
+    let mut ledger = new_ledger::<Testnet2, RocksDB>();
+    let mut other_ledger = new_ledger::<Testnet2, RocksDB>();
+
+    // Initialize a new account.
+    let account = Account::<Testnet2>::new(&mut thread_rng());
+    let address = account.address();
+
+    // Mine one block on the first ledger, and three blocks on the second ledger.
+    let block = ledger.mine_next_block(address, &[], &terminator, rng).expect("Failed to mine");
+    ledger.add_next_block(&block).expect("Failed to add next block to ledger");
+    let mut other_blocks = Vec::with_capacity(3);
+    for _ in 0..3 {
+        let other_block = other_ledger
+            .mine_next_block(address, &[], &terminator, rng)
+            .expect("Failed to mine");
+        other_ledger
+            .add_next_block(&other_block)
+            .expect("Failed to add next block to ledger");
+        other_blocks.push(other_block);
+    }
+
+    // Ensure a branch with more cumulative work, but with an invalid second block, is rejected.
+    let latest_ledger_root = ledger.latest_ledger_root();
+    let latest_block_locators = ledger.latest_block_locators();
+    let branch = vec![other_blocks[0].clone(), other_blocks[2].clone()];
+    assert!(ledger.switch_to_branch(0, &branch).is_err());
+
+    // Ensure the canon chain is unchanged, in memory and in storage.
+    assert_eq!(1, ledger.latest_block_height());
+    assert_eq!(block.hash(), ledger.latest_block_hash());
+    assert_eq!(latest_ledger_root, ledger.latest_ledger_root());
+    assert_eq!(latest_block_locators, ledger.latest_block_locators());
+    assert_eq!(block, ledger.get_block(1).expect("Failed to get block"));
+    assert!(!ledger.contains_block_hash(&other_blocks[0].hash()).unwrap());
+
+    // Ensure the ledger still switches to a valid branch.
+    let removed_blocks = ledger.switch_to_branch(0, &other_blocks).expect("Failed to switch to the branch");
+    assert_eq!(vec![block], removed_blocks);
+    assert_eq!(other_ledger.latest_block_hash(), ledger.latest_block_hash());
+}
+
 #[test]
 fn test_remove_last_block() {
     let rng = &mut thread_rng();
@@ -194,6 +374,45 @@ fn test_remove_last_2_blocks() {
     assert_eq!(ledger_tree.root(), ledger.latest_ledger_root());
 }
 
+#[test]
+fn test_repair() {
+    let rng = &mut thread_rng();
+    let terminator = AtomicBool::new(false);
+
+    // Initialize a new account.
+    let account = Account::<Testnet2>::new(&mut thread_rng());
+    let address = account.address();
+
+    // Initialize a new ledger with two blocks.
+    let directory = temp_dir();
+    let mut ledger = LedgerState::<Testnet2>::open::<RocksDB, _>(&directory, false).expect("Failed to initialize ledger");
+    let block_1 = ledger.mine_next_block(address, &[], &terminator, rng).expect("Failed to mine");
+    ledger.add_next_block(&block_1).expect("Failed to add next block to ledger");
+    let block_2 = ledger.mine_next_block(address, &[], &terminator, rng).expect("Failed to mine");
+    ledger.add_next_block(&block_2).expect("Failed to add next block to ledger");
+    drop(ledger);
+
+    // Remove the block height of the last block, leaving its other entries dangling in storage.
+    {
+        let storage = RocksDB::open(&directory, Testnet2::NETWORK_ID, false).expect("Failed to open storage");
+        let block_heights = storage
+            .open_map::<u32, <Testnet2 as Network>::BlockHash>("block_heights")
+            .expect("Failed to open data map");
+        block_heights.remove(&2).expect("Failed to remove");
+    }
+    assert!(LedgerState::<Testnet2>::open::<RocksDB, _>(&directory, false).is_err());
+
+    // Repair the ledger, and ensure it is reverted to the highest consistent block.
+    let mut ledger = LedgerState::<Testnet2>::repair::<RocksDB, _>(&directory).expect("Failed to repair ledger");
+    assert_eq!(1, ledger.latest_block_height());
+    assert_eq!(block_1.hash(), ledger.latest_block_hash());
+
+    // Ensure the dangling entries of the last block are removed, so that it can be added again.
+    assert!(!ledger.contains_block_hash(&block_2.hash()).expect("Failed to check block hash"));
+    ledger.add_next_block(&block_2).expect("Failed to add next block to ledger");
+    assert_eq!(2, ledger.latest_block_height());
+}
+
 #[test]
 fn test_get_block_locators() {
     let rng = &mut thread_rng();
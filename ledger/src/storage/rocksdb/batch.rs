@@ -0,0 +1,114 @@
+// Copyright (C) 2019-2021 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+
+use anyhow::anyhow;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+/// The writes buffered in an atomic batch.
+#[derive(Default)]
+struct PendingWrites {
+    /// The batch that is written to the database, all at once.
+    batch: rocksdb::WriteBatch,
+    /// The latest buffered value for each written key, where `None` denotes a removal.
+    values: HashMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+/// An atomic batch of writes, shared by the maps opened from the same storage.
+#[derive(Default)]
+pub struct Batch {
+    batch: Arc<Mutex<Option<PendingWrites>>>,
+}
+
+impl Batch {
+    /// Returns a handle to this batch, which observes and buffers the same writes.
+    pub(crate) fn share(&self) -> Self {
+        Self { batch: self.batch.clone() }
+    }
+
+    /// Starts a new batch, returning an error if a batch is already in progress.
+    pub(crate) fn start(&self) -> Result<()> {
+        let mut batch = self.batch.lock();
+        match batch.is_some() {
+            true => Err(anyhow!("An atomic batch is already in progress")),
+            false => {
+                *batch = Some(PendingWrites::default());
+                Ok(())
+            }
+        }
+    }
+
+    /// Discards the batch in progress, if any.
+    pub(crate) fn abort(&self) {
+        *self.batch.lock() = None;
+    }
+
+    /// Writes the batch in progress to the given database, all at once.
+    pub(crate) fn finish(&self, rocksdb: &rocksdb::DB) -> Result<()> {
+        match self.batch.lock().take() {
+            Some(pending) => Ok(rocksdb.write(pending.batch)?),
+            None => Err(anyhow!("No atomic batch is in progress")),
+        }
+    }
+
+    /// Adds the given key-value pair to the batch in progress. Returns `false` if no batch is in progress.
+    pub(crate) fn put(&self, key: &[u8], value: &[u8]) -> bool {
+        match self.batch.lock().as_mut() {
+            Some(pending) => {
+                pending.batch.put(key, value);
+                pending.values.insert(key.to_vec(), Some(value.to_vec()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Adds the removal of the given key to the batch in progress. Returns `false` if no batch is in progress.
+    pub(crate) fn delete(&self, key: &[u8]) -> bool {
+        match self.batch.lock().as_mut() {
+            Some(pending) => {
+                pending.batch.delete(key);
+                pending.values.insert(key.to_vec(), None);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the value buffered for the given key in the batch in progress, where `Some(None)` denotes a removal.
+    /// Returns `None` if no batch is in progress, or if the key has no buffered write.
+    pub(crate) fn get(&self, key: &[u8]) -> Option<Option<Vec<u8>>> {
+        self.batch.lock().as_ref().and_then(|pending| pending.values.get(key).cloned())
+    }
+}
+
+/// A clone starts with its own empty batch, so that the buffered writes of one owner,
+/// which may yet be aborted, are never observed by the clones held by other readers.
+impl Clone for Batch {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for Batch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Batch")
+            .field("is_in_progress", &self.batch.lock().is_some())
+            .finish()
+    }
+}
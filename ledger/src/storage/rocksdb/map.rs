@@ -21,9 +21,28 @@ pub struct DataMap<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOw
     pub(super) rocksdb: Arc<rocksdb::DB>,
     pub(super) context: Vec<u8>,
     pub(super) is_read_only: bool,
+    pub(super) batch: Batch,
     pub(super) _phantom: PhantomData<(K, V)>,
 }
 
+impl<K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> DataMap<K, V> {
+    ///
+    /// Returns a clone of this map, which shares the atomic batch of the given map, instead of starting with its own.
+    ///
+    pub(crate) fn clone_with_batch_of<K2: Serialize + DeserializeOwned, V2: Serialize + DeserializeOwned>(
+        &self,
+        map: &DataMap<K2, V2>,
+    ) -> Self {
+        Self {
+            rocksdb: self.rocksdb.clone(),
+            context: self.context.clone(),
+            is_read_only: self.is_read_only,
+            batch: map.batch.share(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
 impl<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> Map<'a, K, V> for DataMap<K, V> {
     type Iterator = Iter<'a, K, V>;
     type Keys = Keys<'a, K>;
@@ -51,7 +70,13 @@ impl<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> Map<'
         let mut key_buf = self.context.clone();
         key_buf.reserve(bincode::serialized_size(&key)? as usize);
         bincode::serialize_into(&mut key_buf, &key)?;
-        match self.rocksdb.get(&key_buf)? {
+
+        // If an atomic batch is in progress, read the buffered write for the key, if one exists.
+        let data = match self.batch.get(&key_buf) {
+            Some(data) => data,
+            None => self.rocksdb.get(&key_buf)?,
+        };
+        match data {
             Some(data) => Ok(Some(bincode::deserialize(&data)?)),
             None => Ok(None),
         }
@@ -70,7 +95,10 @@ impl<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> Map<'
         bincode::serialize_into(&mut key_buf, &key)?;
         let value_buf = bincode::serialize(value)?;
 
-        self.rocksdb.put(&key_buf, &value_buf)?;
+        // If an atomic batch is in progress, buffer the write in the batch.
+        if !self.batch.put(&key_buf, &value_buf) {
+            self.rocksdb.put(&key_buf, &value_buf)?;
+        }
         Ok(())
     }
 
@@ -86,7 +114,10 @@ impl<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> Map<'
         key_buf.reserve(bincode::serialized_size(&key)? as usize);
         bincode::serialize_into(&mut key_buf, &key)?;
 
-        self.rocksdb.delete(&key_buf)?;
+        // If an atomic batch is in progress, buffer the removal in the batch.
+        if !self.batch.delete(&key_buf) {
+            self.rocksdb.delete(&key_buf)?;
+        }
         Ok(())
     }
 
@@ -120,6 +151,28 @@ impl<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> Map<'
         Values::new(db_iter, self.context.clone())
     }
 
+    ///
+    /// Starts an atomic batch, in which the writes to every map of the same storage are buffered.
+    /// Clones of this map start with their own empty batch.
+    ///
+    fn start_batch(&self) -> Result<()> {
+        self.batch.start()
+    }
+
+    ///
+    /// Discards the writes buffered in the atomic batch.
+    ///
+    fn abort_batch(&self) {
+        self.batch.abort()
+    }
+
+    ///
+    /// Writes the writes buffered in the atomic batch to storage, all at once.
+    ///
+    fn finish_batch(&self) -> Result<()> {
+        self.batch.finish(&self.rocksdb)
+    }
+
     ///
     /// Performs a refresh operation for implementations of `Map` that perform periodic operations.
     /// This method is implemented here for RocksDB to catch up a reader (secondary) database.
@@ -136,4 +189,16 @@ impl<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwned> Map<'
         }
         false
     }
+
+    ///
+    /// Flushes any buffered writes of the map to persistent storage.
+    /// This method is implemented here for RocksDB to flush its memtables to disk.
+    ///
+    fn flush(&self) -> Result<()> {
+        // If the storage is in read-only mode, there are no writes to flush.
+        if !self.is_read_only {
+            self.rocksdb.flush()?;
+        }
+        Ok(())
+    }
 }
@@ -14,6 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+mod batch;
+use batch::*;
+
 mod iterator;
 use iterator::*;
 
@@ -49,6 +52,7 @@ pub struct RocksDB {
     rocksdb: Arc<rocksdb::DB>,
     context: Vec<u8>,
     is_read_only: bool,
+    batch: Batch,
 }
 
 impl Storage for RocksDB {
@@ -83,6 +87,7 @@ impl Storage for RocksDB {
             rocksdb,
             context: context_bytes,
             is_read_only,
+            batch: Default::default(),
         })
     }
 
@@ -102,6 +107,7 @@ impl Storage for RocksDB {
             rocksdb: self.rocksdb.clone(),
             context: context_bytes,
             is_read_only: self.is_read_only,
+            batch: self.batch.share(),
             _phantom: PhantomData,
         })
     }
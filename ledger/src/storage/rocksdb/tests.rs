@@ -111,3 +111,84 @@ fn test_reopen() {
         assert_eq!(Some("123456789".to_string()), map.get(&123456789).expect("Failed to get"));
     }
 }
+
+#[test]
+fn test_insert_and_flush() {
+    let directory = temp_dir();
+    {
+        let storage = RocksDB::open(&directory, 0, false).expect("Failed to open storage");
+        let map = storage.open_map::<u32, String>("hello world").expect("Failed to open data map");
+
+        map.insert(&123456789, &"123456789".to_string()).expect("Failed to insert");
+        map.flush().expect("Failed to flush");
+    }
+    {
+        let storage = RocksDB::open(&directory, 0, false).expect("Failed to open storage");
+        let map = storage.open_map::<u32, String>("hello world").expect("Failed to open data map");
+
+        assert_eq!(Some("123456789".to_string()), map.get(&123456789).expect("Failed to get"));
+    }
+}
+
+#[test]
+fn test_batch_finish() {
+    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map_0 = storage.open_map::<u32, String>("hello world").expect("Failed to open data map");
+    let map_1 = storage.open_map::<u32, String>("hello friend").expect("Failed to open data map");
+
+    map_0.insert(&123456789, &"123456789".to_string()).expect("Failed to insert");
+
+    map_0.start_batch().expect("Failed to start batch");
+    assert!(map_1.start_batch().is_err());
+
+    // The writes of every map are buffered until the batch is finished, and are only observed by reads.
+    map_0.remove(&123456789).expect("Failed to remove");
+    map_1.insert(&987654321, &"987654321".to_string()).expect("Failed to insert");
+    assert!(!map_0.contains_key(&123456789).expect("Failed to call contains key"));
+    assert_eq!(Some("987654321".to_string()), map_1.get(&987654321).expect("Failed to get"));
+    assert_eq!(1, map_0.iter().count());
+    assert_eq!(0, map_1.iter().count());
+
+    map_1.finish_batch().expect("Failed to finish batch");
+    assert!(!map_0.contains_key(&123456789).expect("Failed to call contains key"));
+    assert!(map_1.contains_key(&987654321).expect("Failed to call contains key"));
+    assert!(map_0.finish_batch().is_err());
+}
+
+#[test]
+fn test_batch_abort() {
+    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>("hello world").expect("Failed to open data map");
+
+    map.start_batch().expect("Failed to start batch");
+    map.insert(&123456789, &"123456789".to_string()).expect("Failed to insert");
+    map.abort_batch();
+    assert!(!map.contains_key(&123456789).expect("Failed to call contains key"));
+
+    // Once the batch is aborted, writes are applied immediately.
+    map.insert(&123456789, &"123456789".to_string()).expect("Failed to insert");
+    assert!(map.contains_key(&123456789).expect("Failed to call contains key"));
+}
+
+#[test]
+fn test_batch_clone() {
+    let storage = RocksDB::open(temp_dir(), 0, false).expect("Failed to open storage");
+    let map = storage.open_map::<u32, String>("hello world").expect("Failed to open data map");
+    let map_clone = map.clone();
+
+    map.start_batch().expect("Failed to start batch");
+    map.insert(&123456789, &"123456789".to_string()).expect("Failed to insert");
+
+    // Clones of the map, and of the storage, start with their own empty batch, and do not observe the buffered writes.
+    let other_map = storage
+        .clone()
+        .open_map::<u32, String>("hello world")
+        .expect("Failed to open data map");
+    assert!(!map_clone.contains_key(&123456789).expect("Failed to call contains key"));
+    assert!(!other_map.contains_key(&123456789).expect("Failed to call contains key"));
+    map_clone.start_batch().expect("Failed to start batch");
+    map_clone.abort_batch();
+
+    map.finish_batch().expect("Failed to finish batch");
+    assert!(map_clone.contains_key(&123456789).expect("Failed to call contains key"));
+}
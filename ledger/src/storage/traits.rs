@@ -96,6 +96,23 @@ pub trait Map<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwn
     ///
     fn values(&'a self) -> Self::Values;
 
+    ///
+    /// Starts an atomic batch, in which the inserts and removals of every map of the same storage are buffered,
+    /// until the batch is finished or aborted. Reads of a key observe the buffered writes, while iterators do not.
+    /// Clones of the storage, or of a map, start with their own empty batch, and never observe these writes.
+    ///
+    fn start_batch(&self) -> Result<()>;
+
+    ///
+    /// Discards the writes buffered in the atomic batch.
+    ///
+    fn abort_batch(&self);
+
+    ///
+    /// Writes the writes buffered in the atomic batch to storage, all at once.
+    ///
+    fn finish_batch(&self) -> Result<()>;
+
     ///
     /// Performs a refresh operation for implementations of `Map` that perform periodic operations.
     /// Returns `true` if the database state has been updated.
@@ -104,4 +121,12 @@ pub trait Map<'a, K: Serialize + DeserializeOwned, V: Serialize + DeserializeOwn
         // Currently, this method is implemented for RocksDB to catch up a reader (secondary) database.
         true
     }
+
+    ///
+    /// Flushes any buffered writes of the map to persistent storage.
+    ///
+    fn flush(&self) -> Result<()> {
+        // Currently, this method is implemented for RocksDB to flush its memtables to disk.
+        Ok(())
+    }
 }
@@ -16,7 +16,7 @@
 
 use crate::{
     display::{logs::Logs, overview::Overview},
-    network::Server,
+    network::{MiningStats, Server},
     Environment,
 };
 use snarkvm::dpc::Network;
@@ -65,13 +65,14 @@ impl<'a> TabsState<'a> {
 }
 
 pub(crate) struct Display<'a, N: Network, E: Environment> {
-    server: Server<N, E>,
+    server: &'a Server<N, E>,
     tabs: TabsState<'a>,
     tick_rate: Duration,
+    mining_stats: MiningStats,
 }
 
 impl<'a, N: Network, E: Environment> Display<'a, N, E> {
-    pub fn start(server: Server<N, E>) -> Result<()> {
+    pub fn start(server: &'a Server<N, E>) -> Result<()> {
         enable_raw_mode()?;
         let mut stdout = io::stdout();
         execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
@@ -82,6 +83,7 @@ impl<'a, N: Network, E: Environment> Display<'a, N, E> {
             server,
             tabs: TabsState::new(vec![" Overview ", " Logs "]),
             tick_rate: Duration::from_secs(1),
+            mining_stats: MiningStats::default(),
         };
 
         let res = display.render(&mut terminal);
@@ -108,10 +110,7 @@ impl<'a, N: Network, E: Environment> Display<'a, N, E> {
             if event::poll(timeout)? {
                 if let Event::Key(key) = event::read()? {
                     match key.code {
-                        KeyCode::Esc => {
-                            self.server.shut_down();
-                            return Ok(());
-                        }
+                        KeyCode::Esc => return Ok(()),
                         KeyCode::Left => self.tabs.previous(),
                         KeyCode::Right => self.tabs.next(),
                         _ => {}
@@ -126,7 +125,9 @@ impl<'a, N: Network, E: Environment> Display<'a, N, E> {
         }
     }
 
-    fn heartbeat(&mut self) {}
+    fn heartbeat(&mut self) {
+        self.mining_stats = self.server.mining_history().stats();
+    }
 
     fn draw<B: Backend>(&self, f: &mut Frame<B>) {
         // Initialize the layout of the page.
@@ -165,7 +166,7 @@ impl<'a, N: Network, E: Environment> Display<'a, N, E> {
 
         // Initialize the page.
         match self.tabs.index {
-            0 => Overview.draw(f, chunks[1]),
+            0 => Overview.draw(f, chunks[1], &self.mining_stats),
             1 => Logs.draw(f, chunks[1]),
             _ => unreachable!(),
         };
@@ -14,18 +14,22 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+use crate::network::MiningStats;
+use snarkvm::dpc::AleoAmount;
+
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
     style::Color,
-    widgets::{canvas::Canvas, Block, Borders},
+    text::Spans,
+    widgets::{canvas::Canvas, Block, Borders, Paragraph},
     Frame,
 };
 
 pub(super) struct Overview;
 
 impl Overview {
-    pub(super) fn draw<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+    pub(super) fn draw<B: Backend>(&self, f: &mut Frame<B>, area: Rect, mining_stats: &MiningStats) {
         // Initialize the layout of the page.
         let chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -33,6 +37,7 @@ impl Overview {
                 [
                     Constraint::Length(8),
                     Constraint::Length(10),
+                    Constraint::Length(8),
                     Constraint::Percentage(90),
                     Constraint::Length(3),
                 ]
@@ -54,18 +59,29 @@ impl Overview {
             });
         f.render_widget(canvas, chunks[1]);
 
+        let mining = Paragraph::new(vec![
+            Spans::from(format!("Blocks Mined: {}", mining_stats.blocks_mined)),
+            Spans::from(format!("Canonical: {}", mining_stats.blocks_canonical)),
+            Spans::from(format!("Pending: {}", mining_stats.blocks_pending)),
+            Spans::from(format!("Orphaned: {}", mining_stats.blocks_orphaned)),
+            Spans::from(format!("Total Reward: {}", AleoAmount(mining_stats.total_reward))),
+            Spans::from(format!("Average Time: {} ms", mining_stats.average_time_in_ms)),
+        ])
+        .block(Block::default().borders(Borders::ALL).title("Mining"));
+        f.render_widget(mining, chunks[2]);
+
         let canvas = Canvas::default()
             .block(Block::default().borders(Borders::ALL).title("Logs"))
             .paint(|_ctx| {
                 // ctx.draw(&ball);
             });
-        f.render_widget(canvas, chunks[2]);
+        f.render_widget(canvas, chunks[3]);
 
         let canvas = Canvas::default()
             .block(Block::default().borders(Borders::ALL).title("Help"))
             .paint(|ctx| {
                 ctx.print(0f64, 0f64, "Press ESC to quit", Color::White);
             });
-        f.render_widget(canvas, chunks[3]);
+        f.render_widget(canvas, chunks[4]);
     }
 }
@@ -0,0 +1,297 @@
+// Copyright (C) 2019-2021 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::Environment;
+use snarkvm::dpc::Network;
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::{
+    fs,
+    net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs},
+    path::{Path, PathBuf},
+};
+
+///
+/// The runtime configuration of a node.
+///
+/// Each parameter is initialized to its `Environment` default, which may be overridden
+/// by a configuration file, which may in turn be overridden by CLI flags.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// The directory for storing the ledger and other node state.
+    pub data_dir: PathBuf,
+    /// The IP address to bind the node server to.
+    pub node_bind: IpAddr,
+    /// The port for communicating with the node server.
+    pub node_port: u16,
+    /// The IP address to bind the RPC server to.
+    pub rpc_bind: IpAddr,
+    /// The port for communicating with the RPC server.
+    pub rpc_port: u16,
+    /// The address advertised to peers, if it differs from the bound node server address.
+    pub external_addr: Option<SocketAddr>,
+    /// The list of peer nodes to bootstrap the node server with.
+    pub peer_nodes: Vec<SocketAddr>,
+    /// The list of sync nodes to bootstrap the node server with.
+    pub sync_nodes: Vec<SocketAddr>,
+    /// The duration in seconds to sleep in between heartbeat executions.
+    pub heartbeat_in_secs: u64,
+    /// The duration in seconds to sleep in between ping requests with a connected peer.
+    pub ping_sleep_in_secs: u64,
+    /// The minimum number of peers required to maintain connections with.
+    pub minimum_number_of_peers: usize,
+    /// The maximum number of peers permitted to maintain connections with.
+    pub maximum_number_of_peers: usize,
+    /// The maximum number of blocks that may be fetched in one request.
+    pub maximum_block_request: u32,
+    /// The number of threads used to mine blocks.
+    pub mining_threads: usize,
+}
+
+impl Config {
+    ///
+    /// Initializes a new configuration from the defaults of the given environment.
+    ///
+    pub fn new<E: Environment>() -> Self {
+        Self {
+            data_dir: default_data_dir::<E>(),
+            node_bind: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            node_port: E::DEFAULT_NODE_PORT,
+            rpc_bind: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            rpc_port: E::DEFAULT_RPC_PORT,
+            external_addr: None,
+            peer_nodes: E::PEER_NODES.iter().map(|ip| ip.parse().unwrap()).collect(),
+            sync_nodes: E::SYNC_NODES.iter().map(|ip| ip.parse().unwrap()).collect(),
+            heartbeat_in_secs: E::HEARTBEAT_IN_SECS,
+            ping_sleep_in_secs: E::PING_SLEEP_IN_SECS,
+            minimum_number_of_peers: E::MINIMUM_NUMBER_OF_PEERS,
+            maximum_number_of_peers: E::MAXIMUM_NUMBER_OF_PEERS,
+            maximum_block_request: E::MAXIMUM_BLOCK_REQUEST,
+            mining_threads: num_cpus::get().saturating_sub(1).max(1),
+        }
+    }
+
+    ///
+    /// Loads the configuration file at the given path, layered over the defaults of the given environment.
+    ///
+    pub fn load<E: Environment, P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|error| anyhow!("Failed to read the config file {}: {}", path.display(), error))?;
+        Self::from_toml::<E>(&contents).map_err(|error| anyhow!("Failed to load the config file {}: {}", path.display(), error))
+    }
+
+    ///
+    /// Parses the given TOML string, layered over the defaults of the given environment.
+    ///
+    pub fn from_toml<E: Environment>(contents: &str) -> Result<Self> {
+        let file: ConfigFile = toml::from_str(contents)?;
+
+        let mut config = Self::new::<E>();
+        if let Some(data_dir) = file.data_dir {
+            config.data_dir = data_dir;
+        }
+        if let Some(node_bind) = file.node_bind {
+            config.node_bind = node_bind;
+        }
+        if let Some(node_port) = file.node_port {
+            config.node_port = node_port;
+        }
+        if let Some(rpc_bind) = file.rpc_bind {
+            config.rpc_bind = rpc_bind;
+        }
+        if let Some(rpc_port) = file.rpc_port {
+            config.rpc_port = rpc_port;
+        }
+        if let Some(external_addr) = file.external_addr {
+            config.external_addr = Some(external_addr);
+        }
+        if let Some(peer_nodes) = file.peer_nodes {
+            config.peer_nodes = parse_addresses(&peer_nodes)?;
+        }
+        if let Some(sync_nodes) = file.sync_nodes {
+            config.sync_nodes = parse_addresses(&sync_nodes)?;
+        }
+        if let Some(heartbeat_in_secs) = file.heartbeat_in_secs {
+            config.heartbeat_in_secs = heartbeat_in_secs;
+        }
+        if let Some(ping_sleep_in_secs) = file.ping_sleep_in_secs {
+            config.ping_sleep_in_secs = ping_sleep_in_secs;
+        }
+        if let Some(minimum_number_of_peers) = file.minimum_number_of_peers {
+            config.minimum_number_of_peers = minimum_number_of_peers;
+        }
+        if let Some(maximum_number_of_peers) = file.maximum_number_of_peers {
+            config.maximum_number_of_peers = maximum_number_of_peers;
+        }
+        if let Some(maximum_block_request) = file.maximum_block_request {
+            config.maximum_block_request = maximum_block_request;
+        }
+        if let Some(mining_threads) = file.mining_threads {
+            config.mining_threads = mining_threads;
+        }
+        Ok(config)
+    }
+
+    ///
+    /// Ensures the configuration is well-formed.
+    ///
+    pub fn validate(&self) -> Result<()> {
+        if self.node_port == self.rpc_port {
+            return Err(anyhow!("The node port and RPC port must be distinct (found {})", self.node_port));
+        }
+        if let Some(external_addr) = self.external_addr {
+            if external_addr.ip().is_unspecified() || external_addr.port() == 0 {
+                return Err(anyhow!("The external address {} must be a routable address", external_addr));
+            }
+        }
+        if self.heartbeat_in_secs == 0 {
+            return Err(anyhow!("The heartbeat interval must be at least 1 second"));
+        }
+        if self.maximum_number_of_peers == 0 {
+            return Err(anyhow!("The maximum number of peers must be at least 1"));
+        }
+        if self.minimum_number_of_peers > self.maximum_number_of_peers {
+            return Err(anyhow!(
+                "The minimum number of peers ({}) cannot exceed the maximum number of peers ({})",
+                self.minimum_number_of_peers,
+                self.maximum_number_of_peers
+            ));
+        }
+        if self.maximum_block_request == 0 {
+            return Err(anyhow!("The maximum block request must be at least 1"));
+        }
+        if self.mining_threads == 0 {
+            return Err(anyhow!("The number of mining threads must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+///
+/// The on-disk representation of the configuration file, where every parameter is optional.
+///
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    data_dir: Option<PathBuf>,
+    node_bind: Option<IpAddr>,
+    node_port: Option<u16>,
+    rpc_bind: Option<IpAddr>,
+    rpc_port: Option<u16>,
+    external_addr: Option<SocketAddr>,
+    peer_nodes: Option<Vec<String>>,
+    sync_nodes: Option<Vec<String>>,
+    heartbeat_in_secs: Option<u64>,
+    ping_sleep_in_secs: Option<u64>,
+    minimum_number_of_peers: Option<usize>,
+    maximum_number_of_peers: Option<usize>,
+    maximum_block_request: Option<u32>,
+    mining_threads: Option<usize>,
+}
+
+/// Returns the default data directory for the given environment, i.e. `$XDG_DATA_HOME/snarkos/{network}` on Linux.
+fn default_data_dir<E: Environment>() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("snarkos")
+        .join(<E::Network as Network>::NETWORK_NAME)
+}
+
+/// Resolves the given `host:port` string into a socket address, performing a DNS lookup if the host is not an IP address.
+pub fn resolve_address(address: &str) -> Result<SocketAddr> {
+    if let Ok(socket_addr) = address.parse() {
+        return Ok(socket_addr);
+    }
+    match address.to_socket_addrs() {
+        Ok(mut socket_addrs) => socket_addrs
+            .next()
+            .ok_or_else(|| anyhow!("Failed to resolve the node address '{}'", address)),
+        Err(error) => Err(anyhow!("Invalid node address '{}': {}", address, error)),
+    }
+}
+
+/// Resolves the given list of `host:port` strings into socket addresses.
+fn parse_addresses(addresses: &[String]) -> Result<Vec<SocketAddr>> {
+    addresses.iter().map(|address| resolve_address(address)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+    use snarkvm::dpc::testnet2::Testnet2;
+
+    type E = Client<Testnet2>;
+
+    #[test]
+    fn test_from_toml_empty() {
+        let config = Config::from_toml::<E>("").unwrap();
+        assert_eq!(config, Config::new::<E>());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_from_toml_overrides() {
+        let contents = r#"
+            data_dir = "/tmp/snarkos"
+            node_port = 5000
+            rpc_bind = "127.0.0.1"
+            external_addr = "[2001:db8::1]:4132"
+            sync_nodes = ["10.0.0.1:4132"]
+            maximum_number_of_peers = 50
+            mining_threads = 2
+        "#;
+        let config = Config::from_toml::<E>(contents).unwrap();
+
+        // Check the overridden parameters.
+        assert_eq!(config.data_dir, PathBuf::from("/tmp/snarkos"));
+        assert_eq!(config.node_port, 5000);
+        assert_eq!(config.rpc_bind, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(config.external_addr, Some("[2001:db8::1]:4132".parse().unwrap()));
+        assert_eq!(config.sync_nodes, vec!["10.0.0.1:4132".parse::<SocketAddr>().unwrap()]);
+        assert_eq!(config.maximum_number_of_peers, 50);
+        assert_eq!(config.mining_threads, 2);
+
+        // Check the remaining parameters fall back to the environment defaults.
+        assert_eq!(config.node_bind, IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        assert_eq!(config.rpc_port, <E as Environment>::DEFAULT_RPC_PORT);
+        assert_eq!(config.heartbeat_in_secs, <E as Environment>::HEARTBEAT_IN_SECS);
+        assert_eq!(config.minimum_number_of_peers, <E as Environment>::MINIMUM_NUMBER_OF_PEERS);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_resolve_address() {
+        assert_eq!(resolve_address("127.0.0.1:4132").unwrap(), "127.0.0.1:4132".parse().unwrap());
+        assert_eq!(resolve_address("[::1]:4132").unwrap(), "[::1]:4132".parse().unwrap());
+        assert_eq!(resolve_address("localhost:4132").unwrap().port(), 4132);
+        assert!(resolve_address("127.0.0.1").is_err());
+        assert!(resolve_address("localhost").is_err());
+    }
+
+    #[test]
+    fn test_from_toml_invalid() {
+        // Unknown parameters are rejected.
+        assert!(Config::from_toml::<E>("unknown_parameter = 1").is_err());
+        // Malformed addresses are rejected.
+        assert!(Config::from_toml::<E>(r#"peer_nodes = ["not an address"]"#).is_err());
+        // Inconsistent peer limits are rejected.
+        let config = Config::from_toml::<E>("minimum_number_of_peers = 10\nmaximum_number_of_peers = 5").unwrap();
+        assert!(config.validate().is_err());
+    }
+}
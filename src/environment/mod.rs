@@ -14,6 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
+pub mod config;
+pub use config::*;
+
 use snarkvm::dpc::Network;
 
 use serde::{Deserialize, Serialize};
@@ -38,7 +41,7 @@ pub trait Environment: 'static + Clone + Debug + Default + Send + Sync {
     /// The specified type of node.
     const NODE_TYPE: NodeType;
     /// The version of the network protocol; it can be incremented in order to force users to update.
-    const MESSAGE_VERSION: u32 = 5;
+    const MESSAGE_VERSION: u32 = 7;
 
     /// If `true`, a mining node will craft public coinbase transactions.
     const COINBASE_IS_PUBLIC: bool = false;
@@ -65,8 +68,10 @@ pub trait Environment: 'static + Clone + Debug + Default + Send + Sync {
     /// The duration in seconds after which a connected peer is considered inactive or
     /// disconnected if no message has been received in the meantime.
     const RADIO_SILENCE_IN_SECS: u64 = 120; // 2 minutes
-    /// The duration in seconds after which to expire a failure from a peer.
-    const FAILURE_EXPIRY_TIME_IN_SECS: u64 = 7200; // 2 hours
+    /// The duration in seconds for a peer's misbehaviour score to decay by one point.
+    const MISBEHAVIOUR_DECAY_IN_SECS: u64 = 60;
+    /// The duration in seconds for which a misbehaving peer is banned.
+    const BAN_DURATION_IN_SECS: u64 = 86400; // 24 hours
 
     /// The minimum number of peers required to maintain connections with.
     const MINIMUM_NUMBER_OF_PEERS: usize;
@@ -81,8 +86,19 @@ pub trait Environment: 'static + Clone + Debug + Default + Send + Sync {
     const MAXIMUM_MESSAGE_SIZE: usize = 128 * 1024 * 1024; // 128 MiB
     /// The maximum number of blocks that may be fetched in one request.
     const MAXIMUM_BLOCK_REQUEST: u32 = 50;
-    /// The maximum number of failures tolerated before disconnecting from a peer.
-    const MAXIMUM_NUMBER_OF_FAILURES: usize = 2400;
+    /// The maximum number of block headers that may be fetched in one request.
+    const MAXIMUM_BLOCK_HEADER_REQUEST: u32 = 500;
+    /// The duration in seconds after which an outstanding block request is re-assigned to another peer.
+    const BLOCK_REQUEST_TIMEOUT_IN_SECS: u64 = 30;
+    /// The misbehaviour score at which a peer is banned.
+    const MISBEHAVIOUR_BAN_THRESHOLD: u32 = 100;
+
+    /// The maximum number of unconfirmed transactions held in the memory pool.
+    const MAXIMUM_MEMORY_POOL_TRANSACTIONS: usize = 10_000;
+    /// The maximum total size of the unconfirmed transactions held in the memory pool.
+    const MAXIMUM_MEMORY_POOL_SIZE_IN_BYTES: usize = 64 * 1024 * 1024; // 64 MiB
+    /// The number of blocks after which an unconfirmed transaction is evicted from the memory pool.
+    const MEMORY_POOL_EXPIRY_IN_BLOCKS: u32 = 100;
 }
 
 #[derive(Clone, Debug, Default)]
@@ -76,6 +76,13 @@ impl<K: Clone + PartialEq, V: Clone, const N: u32> CircularMap<K, V, N> {
         }
     }
 
+    ///
+    /// Returns an iterator over the values in the circular map.
+    ///
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.queue.iter().map(|(_, value)| value)
+    }
+
     ///
     /// Inserts the given key-value pair into the circular map.
     ///
@@ -14,16 +14,30 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{helpers::CircularMap, Environment, Message, NodeType, PeersRequest, PeersRouter};
-use snarkos_ledger::{storage::Storage, BlockLocators, LedgerState};
+use crate::{
+    helpers::CircularMap,
+    Config,
+    Environment,
+    MemoryPool,
+    Message,
+    MiningHistory,
+    Misbehaviour,
+    NodeType,
+    PeerBook,
+    PeersRequest,
+    PeersRouter,
+    SideChain,
+};
+use snarkos_ledger::{block_work, storage::Storage, BlockLocators, BlockTemplate, LedgerState};
 use snarkvm::dpc::prelude::*;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chrono::Utc;
-use parking_lot::Mutex;
 use rand::thread_rng;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde::Serialize;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     marker::PhantomData,
     net::SocketAddr,
     path::Path,
@@ -33,10 +47,14 @@ use std::{
     },
     time::{Duration, Instant},
 };
-use tokio::{sync::mpsc, task};
+use tokio::{
+    sync::{mpsc, oneshot, Notify},
+    task,
+};
 
 /// The maximum number of unconfirmed blocks that can be held by the ledger.
-const MAXIMUM_UNCONFIRMED_BLOCKS: u32 = 50;
+/// This also bounds the number of blocks ahead of the latest block that are downloaded in parallel during sync.
+const MAXIMUM_UNCONFIRMED_BLOCKS: u32 = 250;
 
 /// Shorthand for the parent half of the `Ledger` message channel.
 pub(crate) type LedgerRouter<N, E> = mpsc::Sender<LedgerRequest<N, E>>;
@@ -44,11 +62,20 @@ pub(crate) type LedgerRouter<N, E> = mpsc::Sender<LedgerRequest<N, E>>;
 /// Shorthand for the child half of the `Ledger` message channel.
 type LedgerHandler<N, E> = mpsc::Receiver<LedgerRequest<N, E>>;
 
+/// Shorthand for the sender half of the channel that replies with the result of a `SendTransaction` request.
+pub(crate) type TransactionResponder<N> = oneshot::Sender<Result<<N as Network>::TransactionID, TransactionError>>;
+/// Shorthand for the sender half of the channel that replies with the result of a `SubmitBlock` request.
+pub(crate) type BlockResponder<N> = oneshot::Sender<Result<<N as Network>::BlockHash>>;
+
 ///
 /// An enum of requests that the `Ledger` struct processes.
 ///
 #[derive(Debug)]
 pub enum LedgerRequest<N: Network, E: Environment> {
+    /// BlockHeaderRequest := (peer_ip, start_block_height, end_block_height (inclusive))
+    BlockHeaderRequest(SocketAddr, u32, u32),
+    /// BlockHeaderResponse := (peer_ip, \[(block_hash, block_header)\])
+    BlockHeaderResponse(SocketAddr, Vec<(N::BlockHash, BlockHeader<N>)>),
     /// BlockRequest := (peer_ip, start_block_height, end_block_height (inclusive))
     BlockRequest(SocketAddr, u32, u32),
     /// BlockResponse := (peer_ip, block)
@@ -57,21 +84,60 @@ pub enum LedgerRequest<N: Network, E: Environment> {
     Disconnect(SocketAddr),
     /// Heartbeat := ()
     Heartbeat(LedgerRouter<N, E>),
-    /// Mine := (local_ip, miner_address, ledger_router)
-    Mine(SocketAddr, Address<N>, LedgerRouter<N, E>),
+    /// Mine := (local_ip, ledger_router)
+    Mine(SocketAddr, LedgerRouter<N, E>),
     /// Ping := (peer_ip, block_height, block_hash)
     Ping(SocketAddr, u32, N::BlockHash),
-    /// Pong := (peer_ip, is_fork, block_locators)
-    Pong(SocketAddr, Option<bool>, BlockLocators<N>),
+    /// Pong := (peer_ip, is_fork, cumulative_work, block_locators)
+    Pong(SocketAddr, Option<bool>, u128, BlockLocators<N>),
     /// SendPing := (peer_ip)
     SendPing(SocketAddr),
+    /// SendTransaction := (local_ip, transaction, responder)
+    SendTransaction(SocketAddr, Transaction<N>, TransactionResponder<N>),
+    /// SetMiningRecipient := (recipient)
+    SetMiningRecipient(Address<N>),
+    /// SetMiningThreads := (number_of_threads)
+    SetMiningThreads(usize),
+    /// StartMining := ()
+    StartMining,
+    /// StopMining := ()
+    StopMining,
+    /// SubmitBlock := (local_ip, block, responder)
+    SubmitBlock(SocketAddr, Block<N>, BlockResponder<N>),
     /// UnconfirmedBlock := (peer_ip, block)
     UnconfirmedBlock(SocketAddr, Block<N>),
     /// UnconfirmedTransaction := (peer_ip, transaction)
     UnconfirmedTransaction(SocketAddr, Transaction<N>),
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///
+/// An enum of reasons for the ledger to reject an unconfirmed transaction.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum TransactionError {
+    #[error("Transaction {0} is invalid")]
+    Invalid(String),
+    #[error("Transaction {0} already exists in the ledger")]
+    AlreadyInLedger(String),
+    #[error("Transaction {0} already exists in the memory pool")]
+    AlreadyInMemoryPool(String),
+    #[error("Transaction references an unknown ledger root {0}")]
+    UnknownLedgerRoot(String),
+    #[error("Serial number {0} already exists in the ledger")]
+    SpentSerialNumber(String),
+    #[error("Serial number {0} is already spent by a transaction in the memory pool")]
+    ConflictingSerialNumber(String),
+    #[error("Commitment {0} already exists in the ledger")]
+    DuplicateCommitment(String),
+    #[error("Commitment {0} already exists in a transaction in the memory pool")]
+    ConflictingCommitment(String),
+    #[error("Transaction was rejected by the memory pool: {0}")]
+    Rejected(String),
+    #[error("{0}")]
+    Storage(String),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 #[repr(u8)]
 pub enum Status {
     /// The ledger is ready to handle requests.
@@ -98,42 +164,75 @@ pub struct Ledger<N: Network, E: Environment> {
     unconfirmed_blocks: CircularMap<N::BlockHash, Block<N>, { MAXIMUM_UNCONFIRMED_BLOCKS }>,
     /// The pool of unconfirmed transactions.
     memory_pool: MemoryPool<N>,
+    /// The runtime configuration of this node.
+    config: Config,
+    /// The persistent book of peers known to this node, shared with `Peers`.
+    peer_book: PeerBook,
+    /// The persistent history of the blocks mined by this node.
+    mining_history: MiningHistory<N>,
 
     /// The status of the ledger.
     status: Arc<AtomicU8>,
     /// A terminator bit for the miner.
     terminator: Arc<AtomicBool>,
+    /// The recipient of the coinbase rewards, if this node is mining.
+    mining_recipient: Option<Address<N>>,
+    /// A flag indicating whether the miner is enabled, or paused.
+    is_mining_enabled: bool,
+    /// The thread pool used by the miner.
+    mining_pool: Arc<ThreadPool>,
+    /// A signal to the miner that the ledger is ready to mine the next block.
+    miner_signal: Arc<Notify>,
     /// The map of each peer to their ledger state := (is_fork, latest_block_height, block_locators).
-    peers_state: HashMap<SocketAddr, Option<(Option<bool>, u32, BlockLocators<N>)>>,
+    peers_state: HashMap<SocketAddr, Option<(Option<bool>, u32, u128, BlockLocators<N>)>>,
     /// The map of each peer to their block requests := HashMap<(block_height, block_hash), timestamp>
     block_requests: HashMap<SocketAddr, HashMap<(u32, Option<N::BlockHash>), i64>>,
-    /// A lock to ensure methods that need to be mutually-exclusive are enforced.
-    /// In this context, `add_block` and `update_block_requests` must be mutually-exclusive.
-    block_requests_lock: Arc<Mutex<bool>>,
+    /// The map of each peer to the timestamp of their last timed out block request.
+    block_request_timeouts: HashMap<SocketAddr, i64>,
+    /// The outstanding request for the block headers of a fork := (peer_ip, start_block_height, end_block_height, timestamp)
+    block_header_request: Option<(SocketAddr, u32, u32, i64)>,
+    /// The side chain of a fork that is being downloaded, before the ledger switches to it.
+    side_chain: Option<SideChain<N>>,
     /// The timestamp of the last successful block update.
     last_block_update_timestamp: Instant,
-    /// The map of each peer to their failure messages := (failure_message, timestamp).
-    failures: HashMap<SocketAddr, Vec<(String, i64)>>,
     _phantom: PhantomData<E>,
 }
 
 impl<N: Network, E: Environment> Ledger<N, E> {
     /// Initializes a new instance of the ledger.
-    pub fn open<S: Storage, P: AsRef<Path>>(path: P) -> Result<Self> {
+    pub fn open<S: Storage, P: AsRef<Path>>(
+        path: P,
+        config: Config,
+        peer_book: PeerBook,
+        mining_history: MiningHistory<N>,
+    ) -> Result<Self> {
         let canon = LedgerState::open::<S, P>(path, false)?;
+        let mining_pool = Arc::new(Self::build_mining_pool(config.mining_threads)?);
         let last_block_update_timestamp = Instant::now();
         Ok(Self {
             canon,
             unconfirmed_blocks: Default::default(),
-            memory_pool: MemoryPool::new(),
+            memory_pool: MemoryPool::new(
+                E::MAXIMUM_MEMORY_POOL_TRANSACTIONS,
+                E::MAXIMUM_MEMORY_POOL_SIZE_IN_BYTES,
+                E::MEMORY_POOL_EXPIRY_IN_BLOCKS,
+            ),
+            config,
+            peer_book,
+            mining_history,
 
             status: Arc::new(AtomicU8::new(Status::Peering as u8)),
             terminator: Arc::new(AtomicBool::new(false)),
+            mining_recipient: None,
+            is_mining_enabled: true,
+            mining_pool,
+            miner_signal: Arc::new(Notify::new()),
             peers_state: Default::default(),
             block_requests: Default::default(),
-            block_requests_lock: Arc::new(Mutex::new(true)),
+            block_request_timeouts: Default::default(),
+            block_header_request: None,
+            side_chain: None,
             last_block_update_timestamp,
-            failures: Default::default(),
             _phantom: PhantomData,
         })
     }
@@ -155,6 +254,31 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         self.status() == Status::Mining
     }
 
+    /// Returns the signal that wakes the miner, once the ledger is ready to mine the next block.
+    pub(super) fn miner_signal(&self) -> Arc<Notify> {
+        self.miner_signal.clone()
+    }
+
+    /// Returns `true` if the miner is enabled, or `false` if it is paused.
+    pub fn is_mining_enabled(&self) -> bool {
+        self.is_mining_enabled
+    }
+
+    /// Returns the recipient of the coinbase rewards, if this node is mining.
+    pub fn mining_recipient(&self) -> Option<Address<N>> {
+        self.mining_recipient
+    }
+
+    /// Returns the number of threads used by the miner.
+    pub fn mining_threads(&self) -> usize {
+        self.mining_pool.current_num_threads()
+    }
+
+    /// Returns the persistent history of the blocks mined by this node.
+    pub fn mining_history(&self) -> &MiningHistory<N> {
+        &self.mining_history
+    }
+
     /// Returns `true` if the ledger is currently peering.
     pub fn is_peering(&self) -> bool {
         self.status() == Status::Peering
@@ -165,6 +289,11 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         self.status() == Status::Syncing
     }
 
+    /// Returns the maximum number of blocks that may be requested or served in a single range.
+    pub fn maximum_block_request(&self) -> u32 {
+        self.config.maximum_block_request
+    }
+
     /// Returns the latest block height.
     pub fn latest_block_height(&self) -> u32 {
         self.canon.latest_block_height()
@@ -175,19 +304,217 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         self.canon.latest_block_hash()
     }
 
+    /// Returns the parameters for mining the next block on the canonical chain.
+    pub fn get_block_template(&self) -> BlockTemplate<N> {
+        self.canon.get_block_template()
+    }
+
+    /// Returns the unconfirmed transactions in the memory pool, from the highest to the lowest fee-per-byte.
+    pub fn memory_pool_transactions(&self) -> Vec<Transaction<N>> {
+        self.memory_pool.transactions()
+    }
+
+    /// Returns the unconfirmed transaction for the given transaction ID, if it exists in the memory pool.
+    pub fn get_unconfirmed_transaction(&self, transaction_id: &N::TransactionID) -> Option<Transaction<N>> {
+        self.memory_pool.get_transaction(transaction_id).cloned()
+    }
+
+    /// Returns every reason the given transaction may not be added to the memory pool.
+    /// If the returned list is empty, the transaction is valid against the current ledger and memory pool.
+    pub fn validate_transaction(&self, transaction: &Transaction<N>) -> Vec<TransactionError> {
+        let transaction_id = transaction.transaction_id();
+        let mut errors = Vec::new();
+
+        // Ensure the transaction is well-formed.
+        if !transaction.is_valid() {
+            errors.push(TransactionError::Invalid(transaction_id.to_string()));
+        }
+        // Ensure the transaction does not already exist in the canon chain.
+        match self.canon.contains_transaction(&transaction_id) {
+            Ok(true) => errors.push(TransactionError::AlreadyInLedger(transaction_id.to_string())),
+            Ok(false) => (),
+            Err(error) => errors.push(TransactionError::Storage(error.to_string())),
+        }
+        // Ensure the transaction does not already exist in the memory pool.
+        if self.memory_pool.contains_transaction(&transaction_id) {
+            errors.push(TransactionError::AlreadyInMemoryPool(transaction_id.to_string()));
+        }
+        // Ensure the transaction references a valid past or current ledger root.
+        match self.canon.contains_ledger_root(&transaction.ledger_root()) {
+            Ok(true) => (),
+            Ok(false) => errors.push(TransactionError::UnknownLedgerRoot(transaction.ledger_root().to_string())),
+            Err(error) => errors.push(TransactionError::Storage(error.to_string())),
+        }
+        // Ensure the serial numbers are not already spent, in the canon chain or in the memory pool.
+        for serial_number in transaction.serial_numbers() {
+            match self.canon.contains_serial_number(serial_number) {
+                Ok(true) => errors.push(TransactionError::SpentSerialNumber(serial_number.to_string())),
+                Ok(false) => (),
+                Err(error) => errors.push(TransactionError::Storage(error.to_string())),
+            }
+            if self.memory_pool.contains_serial_number(serial_number) {
+                errors.push(TransactionError::ConflictingSerialNumber(serial_number.to_string()));
+            }
+        }
+        // Ensure the commitments do not already exist, in the canon chain or in the memory pool.
+        for commitment in transaction.commitments() {
+            match self.canon.contains_commitment(commitment) {
+                Ok(true) => errors.push(TransactionError::DuplicateCommitment(commitment.to_string())),
+                Ok(false) => (),
+                Err(error) => errors.push(TransactionError::Storage(error.to_string())),
+            }
+            if self.memory_pool.contains_commitment(commitment) {
+                errors.push(TransactionError::ConflictingCommitment(commitment.to_string()));
+            }
+        }
+        errors
+    }
+
+    /// Returns the first reason the given transaction may not be added to the memory pool, if any.
+    /// The checks against the ledger and memory pool run first, so the proofs are only verified last.
+    fn check_transaction(&self, transaction: &Transaction<N>) -> Result<(), TransactionError> {
+        let transaction_id = transaction.transaction_id();
+        let storage_error = |error: anyhow::Error| TransactionError::Storage(error.to_string());
+
+        // Ensure the transaction does not already exist in the canon chain or in the memory pool.
+        if self.canon.contains_transaction(&transaction_id).map_err(storage_error)? {
+            return Err(TransactionError::AlreadyInLedger(transaction_id.to_string()));
+        }
+        if self.memory_pool.contains_transaction(&transaction_id) {
+            return Err(TransactionError::AlreadyInMemoryPool(transaction_id.to_string()));
+        }
+        // Ensure the transaction references a valid past or current ledger root.
+        if !self.canon.contains_ledger_root(&transaction.ledger_root()).map_err(storage_error)? {
+            return Err(TransactionError::UnknownLedgerRoot(transaction.ledger_root().to_string()));
+        }
+        // Ensure the serial numbers are not already spent, in the canon chain or in the memory pool.
+        for serial_number in transaction.serial_numbers() {
+            if self.canon.contains_serial_number(serial_number).map_err(storage_error)? {
+                return Err(TransactionError::SpentSerialNumber(serial_number.to_string()));
+            }
+            if self.memory_pool.contains_serial_number(serial_number) {
+                return Err(TransactionError::ConflictingSerialNumber(serial_number.to_string()));
+            }
+        }
+        // Ensure the commitments do not already exist, in the canon chain or in the memory pool.
+        for commitment in transaction.commitments() {
+            if self.canon.contains_commitment(commitment).map_err(storage_error)? {
+                return Err(TransactionError::DuplicateCommitment(commitment.to_string()));
+            }
+            if self.memory_pool.contains_commitment(commitment) {
+                return Err(TransactionError::ConflictingCommitment(commitment.to_string()));
+            }
+        }
+        // Ensure the transaction is well-formed, which verifies its proofs.
+        match transaction.is_valid() {
+            true => Ok(()),
+            false => Err(TransactionError::Invalid(transaction_id.to_string())),
+        }
+    }
+
+    /// Returns the latest block height known from the connected peers, if any.
+    pub fn latest_peer_block_height(&self) -> Option<u32> {
+        self.peers_state
+            .values()
+            .flatten()
+            .map(|(_, block_height, _, _)| *block_height)
+            .max()
+    }
+
+    /// Returns the number of outstanding block requests.
+    pub fn number_of_block_requests(&self) -> usize {
+        self.block_requests.values().map(|r| r.len()).sum()
+    }
+
+    /// Returns the sync state of each connected peer := (peer_ip, (is_fork, latest_block_height), number_of_block_requests).
+    pub fn peers_sync_state(&self) -> Vec<(SocketAddr, Option<(Option<bool>, u32)>, usize)> {
+        self.peers_state
+            .iter()
+            .map(|(peer_ip, state)| {
+                let state = state.as_ref().map(|(is_fork, block_height, _, _)| (*is_fork, *block_height));
+                let number_of_block_requests = self.block_requests.get(peer_ip).map(|r| r.len()).unwrap_or(0);
+                (*peer_ip, state, number_of_block_requests)
+            })
+            .collect()
+    }
+
     ///
     /// Performs the given `request` to the ledger.
     /// All requests must go through this `update`, so that a unified view is preserved.
     ///
-    pub(super) async fn update(&mut self, request: LedgerRequest<N, E>, peers_router: &PeersRouter<N, E>) {
+    pub(super) async fn update(
+        &mut self,
+        request: LedgerRequest<N, E>,
+        peers_router: &PeersRouter<N, E>,
+        ledger_router: &LedgerRouter<N, E>,
+    ) {
+        // If the node is shutting down, skip the request, to ensure the ledger is not updated after it is flushed.
+        if self.status() == Status::ShuttingDown {
+            trace!("Ledger is shutting down, skipping request");
+            return;
+        }
+
         match request {
+            LedgerRequest::BlockHeaderRequest(peer_ip, start_block_height, end_block_height) => {
+                // Ensure the request is within the accepted limits.
+                let number_of_block_headers = end_block_height.saturating_sub(start_block_height);
+                if number_of_block_headers > E::MAXIMUM_BLOCK_HEADER_REQUEST {
+                    warn!("{} attempted to request {} block headers", peer_ip, number_of_block_headers);
+                    self.add_misbehaviour(peer_ip, Misbehaviour::OversizedBlockRequest);
+                    return;
+                }
+                // Retrieve the requested block hashes and block headers.
+                let block_headers = match (
+                    self.canon.get_block_hashes(start_block_height, end_block_height),
+                    self.canon.get_block_headers(start_block_height, end_block_height),
+                ) {
+                    (Ok(block_hashes), Ok(block_headers)) => block_hashes.into_iter().zip(block_headers).collect(),
+                    (Err(error), _) | (_, Err(error)) => {
+                        error!("{}", error);
+                        self.add_misbehaviour(peer_ip, Misbehaviour::InvalidBlockRequest);
+                        return;
+                    }
+                };
+                // Send a `BlockHeaderResponse` message to the peer.
+                let request = PeersRequest::MessageSend(peer_ip, Message::BlockHeaderResponse(block_headers));
+                if let Err(error) = peers_router.send(request).await {
+                    warn!("[BlockHeaderResponse] {}", error);
+                }
+            }
+            LedgerRequest::BlockHeaderResponse(peer_ip, block_headers) => {
+                // Ensure the block headers were requested from the peer.
+                let (start_block_height, end_block_height) = match self.block_header_request {
+                    Some((header_peer_ip, start_block_height, end_block_height, _)) if header_peer_ip == peer_ip => {
+                        (start_block_height, end_block_height)
+                    }
+                    _ => {
+                        warn!("Received unrequested block headers from {}", peer_ip);
+                        self.add_misbehaviour(peer_ip, Misbehaviour::UnrequestedBlockResponse);
+                        return;
+                    }
+                };
+                self.block_header_request = None;
+
+                // Ensure the block headers start at the requested block height, and do not exceed the requested range,
+                // before validating any of them.
+                let first_block_height = block_headers.first().map(|(_, block_header)| block_header.height());
+                let number_of_block_headers = (end_block_height - start_block_height + 1) as usize;
+                if first_block_height != Some(start_block_height) || block_headers.len() > number_of_block_headers {
+                    warn!("Received block headers outside of the requested range from {}", peer_ip);
+                    self.add_misbehaviour(peer_ip, Misbehaviour::InvalidBlockHeaders);
+                    return;
+                }
+
+                // Download the side chain, or extend it, if the block headers are valid.
+                self.start_side_chain(peer_ip, start_block_height - 1, block_headers, peers_router)
+                    .await;
+            }
             LedgerRequest::BlockRequest(peer_ip, start_block_height, end_block_height) => {
                 // Ensure the request is within the accepted limits.
                 let number_of_blocks = end_block_height.saturating_sub(start_block_height);
-                if number_of_blocks > E::MAXIMUM_BLOCK_REQUEST {
-                    let failure = format!("Attempted to request {} blocks", number_of_blocks);
-                    warn!("{}", failure);
-                    self.add_failure(peer_ip, failure);
+                if number_of_blocks > self.config.maximum_block_request {
+                    warn!("{} attempted to request {} blocks", peer_ip, number_of_blocks);
+                    self.add_misbehaviour(peer_ip, Misbehaviour::OversizedBlockRequest);
                     return;
                 }
                 // Retrieve the requested blocks.
@@ -195,7 +522,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                     Ok(blocks) => blocks,
                     Err(error) => {
                         error!("{}", error);
-                        self.add_failure(peer_ip, format!("{}", error));
+                        self.add_misbehaviour(peer_ip, Misbehaviour::InvalidBlockRequest);
                         return;
                     }
                 };
@@ -211,7 +538,21 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                 // Remove the block request from the ledger.
                 if self.remove_block_request(peer_ip, block.height(), block.hash()) {
                     // On success, process the block response.
-                    self.add_block(block);
+                    // If the block is in the side chain from this peer, add it to the side chain.
+                    let is_side_chain_block = match &self.side_chain {
+                        Some(side_chain) => side_chain.peer_ip() == peer_ip && side_chain.contains_block_hash(&block),
+                        None => false,
+                    };
+                    if is_side_chain_block {
+                        // If the ledger switched to the side chain, fast-forward the ledger with any blocks downloaded ahead of it.
+                        if self.add_side_chain_block(block) {
+                            self.update_ledger();
+                        }
+                    }
+                    // If the block extends the canon chain, fast-forward the ledger with any blocks downloaded ahead of it.
+                    else if self.add_block(block, Some(peer_ip)) {
+                        self.update_ledger();
+                    }
                     // Check if syncing with this peer is complete.
                     if let Some(requests) = self.block_requests.get(&peer_ip) {
                         if requests.is_empty() {
@@ -239,16 +580,14 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                 self.update_status();
                 // Remove expired block requests.
                 self.remove_expired_block_requests();
-                // Remove expired failures.
-                self.remove_expired_failures();
-                // Disconnect from peers with frequent failures.
-                self.disconnect_from_failing_peers(&ledger_router).await;
+                // Disconnect from peers who have been banned.
+                self.disconnect_from_banned_peers(peers_router, &ledger_router).await;
                 // Update the block requests.
                 self.update_block_requests(peers_router).await;
             }
-            LedgerRequest::Mine(local_ip, recipient, ledger_router) => {
+            LedgerRequest::Mine(local_ip, ledger_router) => {
                 // Process the request to mine the next block.
-                self.mine_next_block(local_ip, recipient, ledger_router);
+                self.mine_next_block(local_ip, ledger_router);
             }
             LedgerRequest::Ping(peer_ip, block_height, block_hash) => {
                 // Determine if the peer is on a fork (or unknown).
@@ -256,26 +595,37 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                     Ok(expected_block_hash) => Some(expected_block_hash != block_hash),
                     Err(_) => None,
                 };
+                // Retrieve the cumulative work of the canon chain.
+                let cumulative_work = match self.canon.latest_cumulative_work() {
+                    Ok(cumulative_work) => cumulative_work,
+                    Err(error) => {
+                        error!("{}", error);
+                        return;
+                    }
+                };
                 // Send a `Pong` message to the peer.
-                let request = PeersRequest::MessageSend(peer_ip, Message::Pong(is_fork, self.canon.latest_block_locators()));
+                let message = Message::Pong(is_fork, cumulative_work, self.canon.latest_block_locators());
+                let request = PeersRequest::MessageSend(peer_ip, message);
                 if let Err(error) = peers_router.send(request).await {
                     warn!("[Pong] {}", error);
                 }
             }
-            LedgerRequest::Pong(peer_ip, is_fork, block_locators) => {
+            LedgerRequest::Pong(peer_ip, is_fork, cumulative_work, block_locators) => {
                 // Ensure the peer has been initialized in the ledger.
                 self.initialize_peer(peer_ip);
                 // Process the pong.
-                self.update_peer(peer_ip, is_fork, block_locators).await;
-
-                // Sleep for the preset time before sending a `Ping` request.
-                tokio::time::sleep(Duration::from_secs(E::PING_SLEEP_IN_SECS)).await;
-                // Send a `Ping` request to the peer.
-                let message = Message::Ping(E::MESSAGE_VERSION, self.latest_block_height(), self.latest_block_hash());
-                let request = PeersRequest::MessageSend(peer_ip, message);
-                if let Err(error) = peers_router.send(request).await {
-                    warn!("[Ping] {}", error);
-                }
+                self.update_peer(peer_ip, is_fork, cumulative_work, block_locators).await;
+
+                // Sleep for the preset time before sending a `Ping` request, without holding up the ledger.
+                let ping_sleep = Duration::from_secs(self.config.ping_sleep_in_secs);
+                let ledger_router = ledger_router.clone();
+                task::spawn(async move {
+                    tokio::time::sleep(ping_sleep).await;
+                    // Route a `SendPing` to the ledger.
+                    if let Err(error) = ledger_router.send(LedgerRequest::SendPing(peer_ip)).await {
+                        warn!("[Ping] {}", error);
+                    }
+                });
             }
             LedgerRequest::SendPing(peer_ip) => {
                 // Send a `Ping` request to the peer.
@@ -285,6 +635,58 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                     warn!("[Ping] {}", error);
                 }
             }
+            LedgerRequest::SetMiningRecipient(recipient) => {
+                info!("Setting the mining recipient to {}", recipient);
+                self.mining_recipient = Some(recipient);
+                // If a block is being mined for the previous recipient, set the terminator bit to `true` to abort it.
+                // The miner resumes with the new recipient once the heartbeat updates the status.
+                if self.status() == Status::Mining {
+                    self.terminator.store(true, Ordering::SeqCst);
+                }
+                // Otherwise, wake the miner, in case it was waiting for a recipient.
+                self.signal_miner();
+            }
+            LedgerRequest::SetMiningThreads(number_of_threads) => {
+                // The new thread pool is used from the next block onwards.
+                match Self::build_mining_pool(number_of_threads) {
+                    Ok(mining_pool) => {
+                        info!("Setting the number of mining threads to {}", number_of_threads);
+                        self.mining_pool = Arc::new(mining_pool);
+                    }
+                    Err(error) => warn!("[SetMiningThreads] {}", error),
+                }
+            }
+            LedgerRequest::StartMining => {
+                info!("Resuming the miner");
+                self.is_mining_enabled = true;
+                // Update the status, to ensure the terminator bit is reset if the node is ready to mine.
+                self.update_status();
+            }
+            LedgerRequest::StopMining => {
+                info!("Pausing the miner");
+                self.is_mining_enabled = false;
+                // Set the terminator bit to `true` to ensure it stops mining.
+                self.terminator.store(true, Ordering::SeqCst);
+            }
+            LedgerRequest::SubmitBlock(local_ip, block, responder) => {
+                // Process the block mined by this node, or submitted through RPC, without penalizing this node if it is invalid.
+                let (block_height, block_hash) = (block.height(), block.hash());
+                let result = match self.add_block(block.clone(), None) {
+                    true => {
+                        // Propagate the block to the connected peers.
+                        let request = PeersRequest::MessagePropagate(local_ip, Message::UnconfirmedBlock(block));
+                        if let Err(error) = peers_router.send(request).await {
+                            warn!("[SubmitBlock] {}", error);
+                        }
+                        Ok(block_hash)
+                    }
+                    false => Err(anyhow!("Block {} ({}) was not added to the canon chain", block_height, block_hash)),
+                };
+                // Reply with the result.
+                if responder.send(result).is_err() {
+                    warn!("[SubmitBlock] Failed to reply for block {}", block_hash);
+                }
+            }
             LedgerRequest::UnconfirmedBlock(peer_ip, block) => {
                 // Ensure the given block is new.
                 if let Ok(true) = self.canon.contains_block_hash(&block.hash()) {
@@ -295,7 +697,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                     // Ensure the unconfirmed block is at least within 10 blocks of the latest block height.
                     if block.height() + 10 > self.latest_block_height() {
                         // Process the unconfirmed block.
-                        self.add_block(block.clone());
+                        self.add_block(block.clone(), Some(peer_ip));
                         // Propagate the unconfirmed block to the connected peers.
                         let request = PeersRequest::MessagePropagate(peer_ip, Message::UnconfirmedBlock(block));
                         if let Err(error) = peers_router.send(request).await {
@@ -304,13 +706,35 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                     }
                 }
             }
+            LedgerRequest::SendTransaction(local_ip, transaction, responder) => {
+                // Process the transaction, and reply with the result.
+                let transaction_id = transaction.transaction_id();
+                let result = self.add_unconfirmed_transaction(local_ip, transaction, peers_router).await;
+                if responder.send(result.map(|()| transaction_id)).is_err() {
+                    warn!("[SendTransaction] Failed to reply for transaction {}", transaction_id);
+                }
+            }
             LedgerRequest::UnconfirmedTransaction(peer_ip, transaction) => {
                 // Process the unconfirmed transaction.
-                self.add_unconfirmed_transaction(peer_ip, transaction, peers_router).await
+                if let Err(error) = self.add_unconfirmed_transaction(peer_ip, transaction, peers_router).await {
+                    debug!("Skipping unconfirmed transaction from {}: {}", peer_ip, error);
+                }
             }
         }
     }
 
+    ///
+    /// Stops the miner, halts further updates to the ledger, and flushes the ledger to storage.
+    ///
+    pub(super) fn shut_down(&self) -> Result<()> {
+        // Set the status to `ShuttingDown`, to ensure the ledger stops processing requests.
+        self.status.store(Status::ShuttingDown as u8, Ordering::SeqCst);
+        // Set the terminator bit to `true` to ensure it stops mining.
+        self.terminator.store(true, Ordering::SeqCst);
+        // Flush the ledger to storage.
+        self.canon.flush()
+    }
+
     ///
     /// Attempt to fast-forward the ledger with unconfirmed blocks.
     ///
@@ -341,7 +765,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
             }
             // Attempt to add the unconfirmed block.
             else {
-                match self.add_block(block.clone()) {
+                match self.add_block(block.clone(), None) {
                     // Upon success, remove the unconfirmed block, as it is now confirmed.
                     true => self.unconfirmed_blocks.remove(&block.hash()),
                     false => break,
@@ -354,7 +778,6 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         if self.is_syncing() && self.last_block_update_timestamp.elapsed() > Duration::from_secs(E::RADIO_SILENCE_IN_SECS) {
             trace!("Ledger state has become stale, clearing queue and reverting by one block");
             self.unconfirmed_blocks = Default::default();
-            self.memory_pool = MemoryPool::new();
             self.block_requests.values_mut().for_each(|requests| *requests = Default::default());
             self.revert_to_block_height(self.latest_block_height().saturating_sub(1));
         }
@@ -375,7 +798,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
             return;
         }
         // If there is an insufficient number of connected peers, set the status to `Peering`.
-        else if self.peers_state.len() < E::MINIMUM_NUMBER_OF_PEERS {
+        else if self.peers_state.len() < self.config.minimum_number_of_peers {
             status = Status::Peering;
         }
         // If the ledger is out of date, set the status to `Syncing`.
@@ -386,25 +809,24 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                 _ => Status::Ready,
             };
 
-            // Retrieve the latest block height of this node.
-            let latest_block_height = self.latest_block_height();
+            // Retrieve the cumulative work of this node, and the work of its latest block.
+            let latest_cumulative_work = self.canon.latest_cumulative_work().unwrap_or_default();
+            let latest_block_work = block_work(self.canon.latest_block_difficulty_target());
             // Iterate through the connected peers, to determine if the ledger state is out of date.
             for (_, ledger_state) in self.peers_state.iter() {
-                if let Some((_, block_height, _)) = ledger_state {
-                    if *block_height > latest_block_height {
-                        // Sync if this ledger has fallen behind by 3 or more blocks.
-                        if block_height - latest_block_height > 2 {
-                            // Set the status to `Syncing`.
-                            status = Status::Syncing;
-                            break;
-                        }
+                if let Some((_, _, cumulative_work, _)) = ledger_state {
+                    // Sync if this ledger has fallen behind by more than the work of 2 blocks.
+                    if *cumulative_work > latest_cumulative_work.saturating_add(latest_block_work.saturating_mul(2)) {
+                        // Set the status to `Syncing`.
+                        status = Status::Syncing;
+                        break;
                     }
                 }
             }
         }
 
-        // If the node is `Peering` or `Syncing`, it should not be mining (yet).
-        if status == Status::Peering || status == Status::Syncing {
+        // If the node is `Peering` or `Syncing`, or the miner is paused, it should not be mining.
+        if status == Status::Peering || status == Status::Syncing || !self.is_mining_enabled {
             // Set the terminator bit to `true` to ensure it does not mine.
             self.terminator.store(true, Ordering::SeqCst);
         } else {
@@ -414,18 +836,43 @@ impl<N: Network, E: Environment> Ledger<N, E> {
 
         // Update the ledger to the determined status.
         self.status.store(status as u8, Ordering::SeqCst);
+        // Wake the miner, if the ledger is ready to mine the next block.
+        self.signal_miner();
+    }
+
+    ///
+    /// Wakes the miner, if this node is allowed to mine and is not already mining.
+    ///
+    fn signal_miner(&self) {
+        if E::NODE_TYPE == NodeType::Miner
+            && self.is_mining_enabled
+            && self.mining_recipient.is_some()
+            && self.status() == Status::Ready
+            && !self.terminator.load(Ordering::SeqCst)
+        {
+            self.miner_signal.notify_one();
+        }
     }
 
     ///
     /// Mines a new block and adds it to the canon blocks.
     ///
-    fn mine_next_block(&self, local_ip: SocketAddr, recipient: Address<N>, ledger_router: LedgerRouter<N, E>) {
+    fn mine_next_block(&self, local_ip: SocketAddr, ledger_router: LedgerRouter<N, E>) {
         // If the node type is not a miner, it should not be mining.
         if E::NODE_TYPE != NodeType::Miner {
             return;
         }
+        // If the miner is paused, it should not be mining.
+        if !self.is_mining_enabled {
+            return;
+        }
+        // If there is no recipient for the coinbase rewards, it should not be mining.
+        let recipient = match self.mining_recipient {
+            Some(recipient) => recipient,
+            None => return,
+        };
         // If there is an insufficient number of connected peers, it should not be mining.
-        if self.peers_state.len() < E::MINIMUM_NUMBER_OF_PEERS {
+        if self.peers_state.len() < self.config.minimum_number_of_peers {
             return;
         }
         // If `terminator` is `true`, it should not be mining.
@@ -442,21 +889,42 @@ impl<N: Network, E: Environment> Ledger<N, E> {
             let unconfirmed_transactions = self.memory_pool.transactions();
             let terminator = self.terminator.clone();
             let status = self.status.clone();
+            let mining_pool = self.mining_pool.clone();
+            let mining_history = self.mining_history.clone();
 
             task::spawn(async move {
-                // Mine the next block.
-                let result = canon.mine_next_block(recipient, &unconfirmed_transactions, &terminator, &mut thread_rng());
+                // Mine the next block on the miner thread pool, without blocking the async runtime.
+                let timer = Instant::now();
+                let (sender, receiver) = oneshot::channel();
+                mining_pool.spawn(move || {
+                    let _ = sender.send(canon.mine_next_block(recipient, &unconfirmed_transactions, &terminator, &mut thread_rng()));
+                });
+                let result = match receiver.await {
+                    Ok(result) => result,
+                    Err(_) => Err(anyhow!("The miner stopped before finishing the block")),
+                };
 
-                // Set the status to `Ready`.
-                status.store(Status::Ready as u8, Ordering::SeqCst);
+                // Set the status to `Ready`, if the status has not changed (e.g. the node is shutting down).
+                let _ = status.compare_exchange(Status::Mining as u8, Status::Ready as u8, Ordering::SeqCst, Ordering::SeqCst);
 
                 match result {
                     Ok(block) => {
                         trace!("Miner has found the next block");
-                        // Broadcast the next block.
-                        let request = LedgerRequest::UnconfirmedBlock(local_ip, block);
+                        // Record the mined block in the mining history.
+                        if let Err(error) = mining_history.add_mined_block(&block, timer.elapsed()) {
+                            warn!("Failed to record mined block {}: {}", block.height(), error);
+                        }
+                        // Broadcast the next block, and wait for the ledger to process it.
+                        let (responder, receiver) = oneshot::channel();
+                        let request = LedgerRequest::SubmitBlock(local_ip, block, responder);
                         if let Err(error) = ledger_router.send(request).await {
                             warn!("Failed to broadcast mined block: {}", error);
+                            return;
+                        }
+                        match receiver.await {
+                            Ok(Ok(block_hash)) => debug!("Added mined block {} to the canon chain", block_hash),
+                            Ok(Err(error)) => warn!("Mined block was rejected: {}", error),
+                            Err(_) => warn!("The ledger did not process the mined block"),
                         }
                     }
                     Err(error) => trace!("{}", error),
@@ -465,17 +933,28 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         }
     }
 
+    ///
+    /// Returns a new thread pool for the miner, with the given number of threads.
+    ///
+    fn build_mining_pool(number_of_threads: usize) -> Result<ThreadPool> {
+        if number_of_threads == 0 {
+            return Err(anyhow!("The number of mining threads must be at least 1"));
+        }
+        Ok(ThreadPoolBuilder::new()
+            .num_threads(number_of_threads)
+            .thread_name(|index| format!("snarkos-miner-{}", index))
+            .build()?)
+    }
+
     ///
     /// Adds the given block:
     ///     1) as the next block in the ledger if the block height increments by one, or
     ///     2) to the memory pool for later use.
     ///
     /// Returns `true` if the given block is successfully added to the *canon* chain.
+    /// If the block is invalid and was received from a peer, the peer is penalized.
     ///
-    fn add_block(&mut self, block: Block<N>) -> bool {
-        // Acquire the lock for block requests.
-        let _ = self.block_requests_lock.lock();
-
+    fn add_block(&mut self, block: Block<N>, peer_ip: Option<SocketAddr>) -> bool {
         // Ensure the given block is new.
         if let Ok(true) = self.canon.contains_block_hash(&block.hash()) {
             trace!("Canon chain already contains block {}", block.height());
@@ -488,16 +967,29 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                     self.last_block_update_timestamp = Instant::now();
                     // Set the terminator bit to `true` to ensure the miner updates state.
                     self.terminator.store(true, Ordering::SeqCst);
-                    // On success, filter the memory pool of its transactions, if they exist.
+                    // On success, filter the memory pool of its transactions, and of any transactions they conflict with.
                     self.memory_pool.remove_transactions(block.transactions());
+                    // On success, evict the expired transactions from the memory pool.
+                    for transaction_id in self.memory_pool.remove_expired(block.height()) {
+                        debug!("Evicted expired transaction {} from the memory pool", transaction_id);
+                    }
                     // On success, filter the unconfirmed blocks of this block, if it exists.
                     if self.unconfirmed_blocks.contains_key(&block.hash()) {
                         self.unconfirmed_blocks.remove(&block.hash());
                     }
+                    // On success, update the state of the blocks mined by this node.
+                    if let Err(error) = self.mining_history.set_canonical(block.height(), &block.hash()) {
+                        warn!("Failed to update the mining history: {}", error);
+                    }
 
                     return true;
                 }
-                Err(error) => warn!("{}", error),
+                Err(error) => {
+                    warn!("{}", error);
+                    if let Some(peer_ip) = peer_ip {
+                        self.add_misbehaviour(peer_ip, Misbehaviour::InvalidBlock);
+                    }
+                }
             }
         } else {
             // Ensure the unconfirmed block is well-formed.
@@ -514,7 +1006,12 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                         false => trace!("Unconfirmed block {} already exists in the memory pool", block.height()),
                     }
                 }
-                false => warn!("Unconfirmed block {} is invalid", block.height()),
+                false => {
+                    warn!("Unconfirmed block {} is invalid", block.height());
+                    if let Some(peer_ip) = peer_ip {
+                        self.add_misbehaviour(peer_ip, Misbehaviour::InvalidBlock);
+                    }
+                }
             }
         }
         false
@@ -523,24 +1020,29 @@ impl<N: Network, E: Environment> Ledger<N, E> {
     ///
     /// Adds the given unconfirmed transaction to the memory pool.
     ///
-    async fn add_unconfirmed_transaction(&mut self, peer_ip: SocketAddr, transaction: Transaction<N>, peers_router: &PeersRouter<N, E>) {
+    async fn add_unconfirmed_transaction(
+        &mut self,
+        peer_ip: SocketAddr,
+        transaction: Transaction<N>,
+        peers_router: &PeersRouter<N, E>,
+    ) -> Result<(), TransactionError> {
         // Process the unconfirmed transaction.
         trace!("Received unconfirmed transaction {} from {}", transaction.transaction_id(), peer_ip);
-        // Ensure the unconfirmed transaction is new.
-        if let Ok(false) = self.canon.contains_transaction(&transaction.transaction_id()) {
-            debug!("Adding unconfirmed transaction {} to memory pool", transaction.transaction_id());
-            // Attempt to add the unconfirmed transaction to the memory pool.
-            match self.memory_pool.add_transaction(&transaction) {
-                Ok(()) => {
-                    // Upon success, propagate the unconfirmed transaction to the connected peers.
-                    let request = PeersRequest::MessagePropagate(peer_ip, Message::UnconfirmedTransaction(transaction));
-                    if let Err(error) = peers_router.send(request).await {
-                        warn!("[UnconfirmedTransaction] {}", error);
-                    }
-                }
-                Err(error) => error!("{}", error),
-            }
+        // Ensure the unconfirmed transaction is valid and new.
+        self.check_transaction(&transaction)?;
+
+        debug!("Adding unconfirmed transaction {} to memory pool", transaction.transaction_id());
+        // Attempt to add the unconfirmed transaction to the memory pool.
+        for transaction_id in self.memory_pool.add_transaction(&transaction, self.latest_block_height())? {
+            debug!("Evicted transaction {} from the memory pool", transaction_id);
+        }
+
+        // Upon success, propagate the unconfirmed transaction to the connected peers.
+        let request = PeersRequest::MessagePropagate(peer_ip, Message::UnconfirmedTransaction(transaction));
+        if let Err(error) = peers_router.send(request).await {
+            warn!("[UnconfirmedTransaction] {}", error);
         }
+        Ok(())
     }
 
     ///
@@ -557,11 +1059,18 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                 self.terminator.store(true, Ordering::SeqCst);
 
                 // Ensure the removed blocks are not in the unconfirmed blocks.
-                for removed_block in removed_blocks {
+                for removed_block in &removed_blocks {
                     if self.unconfirmed_blocks.contains_key(&removed_block.hash()) {
                         self.unconfirmed_blocks.remove(&removed_block.hash());
                     }
+                    // Mark the removed block as orphaned, if it was mined by this node.
+                    if let Err(error) = self.mining_history.set_orphaned(&removed_block.hash()) {
+                        warn!("Failed to update the mining history: {}", error);
+                    }
                 }
+
+                // Return the transactions of the removed blocks to the memory pool.
+                self.reinject_transactions(&removed_blocks);
                 true
             }
             Err(error) => {
@@ -577,13 +1086,56 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         }
     }
 
+    ///
+    /// Re-validates the transactions of the given removed blocks against the canon chain,
+    /// and returns the valid ones to the memory pool, so they may be mined again.
+    ///
+    /// The removed blocks are expected in ascending order of block height, so that each transaction
+    /// is validated after the transactions it may depend on. Coinbase transactions are discarded.
+    ///
+    fn reinject_transactions(&mut self, removed_blocks: &[Block<N>]) {
+        let latest_block_height = self.latest_block_height();
+        let (mut reinjected, mut dropped) = (0, 0);
+
+        for transaction in removed_blocks.iter().flat_map(|block| block.transactions().iter()) {
+            // Skip the coinbase transactions, as their rewards are only valid in the removed blocks.
+            if transaction.value_balance().0 < 0 {
+                continue;
+            }
+            // Ensure the transaction is still valid against the canon chain and memory pool.
+            if let Err(error) = self.check_transaction(transaction) {
+                trace!("Dropping reverted transaction {}: {}", transaction.transaction_id(), error);
+                dropped += 1;
+                continue;
+            }
+            match self.memory_pool.add_transaction(transaction, latest_block_height) {
+                Ok(evicted) => {
+                    reinjected += 1;
+                    for transaction_id in evicted {
+                        debug!("Evicted transaction {} from the memory pool", transaction_id);
+                    }
+                }
+                Err(error) => {
+                    trace!("Dropping reverted transaction {}: {}", transaction.transaction_id(), error);
+                    dropped += 1;
+                }
+            }
+        }
+
+        if reinjected + dropped > 0 {
+            debug!(
+                "Returned {} reverted transactions to the memory pool ({} dropped)",
+                reinjected, dropped
+            );
+        }
+    }
+
     ///
     /// Adds an entry for the given peer IP to every data structure in `State`.
     ///
     fn initialize_peer(&mut self, peer_ip: SocketAddr) {
         self.peers_state.entry(peer_ip).or_insert(None);
         self.block_requests.entry(peer_ip).or_insert_with(Default::default);
-        self.failures.entry(peer_ip).or_insert_with(Default::default);
     }
 
     ///
@@ -596,25 +1148,36 @@ impl<N: Network, E: Environment> Ledger<N, E> {
         if self.block_requests.contains_key(peer_ip) {
             self.block_requests.remove(peer_ip);
         }
-        if self.failures.contains_key(peer_ip) {
-            self.failures.remove(peer_ip);
+        if self.block_request_timeouts.contains_key(peer_ip) {
+            self.block_request_timeouts.remove(peer_ip);
+        }
+        if let Some((header_peer_ip, _, _, _)) = self.block_header_request {
+            if header_peer_ip == *peer_ip {
+                self.block_header_request = None;
+            }
+        }
+        if let Some(side_chain) = &self.side_chain {
+            if side_chain.peer_ip() == *peer_ip {
+                self.side_chain = None;
+            }
         }
     }
 
     ///
     /// Updates the state of the given peer.
     ///
-    async fn update_peer(&mut self, peer_ip: SocketAddr, is_fork: Option<bool>, block_locators: BlockLocators<N>) {
+    async fn update_peer(&mut self, peer_ip: SocketAddr, is_fork: Option<bool>, cumulative_work: u128, block_locators: BlockLocators<N>) {
         // Ensure the list of block locators is not empty.
         if block_locators.is_empty() {
-            self.add_failure(peer_ip, "Received a sync response with no block locators".to_string());
+            warn!("Received a sync response with no block locators from {}", peer_ip);
+            self.add_misbehaviour(peer_ip, Misbehaviour::InvalidBlockLocators);
         } else {
             // Ensure the peer provided well-formed block locators.
             match self.canon.check_block_locators(&block_locators) {
                 Ok(is_valid) => {
                     if !is_valid {
                         warn!("Invalid block locators from {}", peer_ip);
-                        self.add_failure(peer_ip, "Invalid block locators".to_string());
+                        self.add_misbehaviour(peer_ip, Misbehaviour::InvalidBlockLocators);
                         return;
                     }
                 }
@@ -633,7 +1196,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                     if expected_block_height != *block_height {
                         let error = format!("Invalid block height {} for block hash {}", expected_block_height, block_hash);
                         trace!("{}", error);
-                        self.add_failure(peer_ip, error);
+                        self.add_misbehaviour(peer_ip, Misbehaviour::InvalidBlockLocators);
                         return;
                     } else {
                         // Update the common ancestor, as this block hash exists in this ledger.
@@ -649,25 +1212,80 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                 }
             }
 
+            // Ensure the cumulative work claimed by the peer is justified by its block locators.
+            match self.check_cumulative_work(common_ancestor, cumulative_work, &block_locators) {
+                Ok(true) => (),
+                Ok(false) => {
+                    warn!("The cumulative work claimed by {} exceeds the work of its block headers", peer_ip);
+                    self.add_misbehaviour(peer_ip, Misbehaviour::InvalidCumulativeWork);
+                    return;
+                }
+                Err(error) => {
+                    warn!("Error checking cumulative work: {}", error);
+                    return;
+                }
+            }
+
             // trace!("STATUS {:?} {} {}", self.status(), self.latest_block_height(), self.number_of_block_requests());
             debug!(
-                "Peer {} is at block {} (common_ancestor = {})",
-                peer_ip, latest_block_height_of_peer, common_ancestor,
+                "Peer {} is at block {} (common_ancestor = {}, cumulative_work = {})",
+                peer_ip, latest_block_height_of_peer, common_ancestor, cumulative_work,
             );
 
             match self.peers_state.get_mut(&peer_ip) {
-                Some(status) => *status = Some((is_fork, latest_block_height_of_peer, block_locators)),
-                None => self.add_failure(peer_ip, format!("Missing ledger state for {}", peer_ip)),
+                Some(status) => *status = Some((is_fork, latest_block_height_of_peer, cumulative_work, block_locators)),
+                None => warn!("Missing ledger state for {}", peer_ip),
             };
         }
     }
 
     ///
-    /// Proceeds to send block requests to a connected peer, if the ledger is out of date.
+    /// Returns `true` if the given cumulative work, claimed by a peer, is justified by its block locators.
+    ///
+    /// A claim that does not exceed the cumulative work of the canon chain is not acted upon, and is accepted as is.
+    /// Otherwise, the claim is bounded by the cumulative work at the common ancestor, plus the work of the block headers
+    /// after the common ancestor, whose proofs of work are verified. As the block locators only contain the block headers
+    /// of the latest blocks, the work of each remaining block is bounded by twice the largest work of a known block,
+    /// to allow for changes in the difficulty target.
     ///
-    /// Case 1 - You are ahead of your peer:
+    fn check_cumulative_work(&self, common_ancestor: u32, cumulative_work: u128, block_locators: &BlockLocators<N>) -> Result<bool> {
+        if cumulative_work <= self.canon.latest_cumulative_work()? {
+            return Ok(true);
+        }
+
+        let mut maximum_cumulative_work = self.canon.get_cumulative_work(common_ancestor)?;
+        let mut maximum_block_work = block_work(self.canon.latest_block_difficulty_target());
+        let mut number_of_unknown_blocks = block_locators.keys().max().copied().unwrap_or(0).saturating_sub(common_ancestor);
+
+        for (block_height, (_, block_header)) in block_locators.range(common_ancestor + 1..) {
+            if let Some(block_header) = block_header {
+                // Ensure the block header is valid, including its proof of work.
+                if block_header.height() != *block_height || !block_header.is_valid() {
+                    return Ok(false);
+                }
+                let work = block_work(block_header.difficulty_target());
+                maximum_cumulative_work = maximum_cumulative_work.saturating_add(work);
+                maximum_block_work = maximum_block_work.max(work);
+                number_of_unknown_blocks = number_of_unknown_blocks.saturating_sub(1);
+            }
+        }
+        let maximum_unknown_work = maximum_block_work.saturating_mul(2).saturating_mul(number_of_unknown_blocks.into());
+        maximum_cumulative_work = maximum_cumulative_work.saturating_add(maximum_unknown_work);
+
+        Ok(cumulative_work <= maximum_cumulative_work)
+    }
+
+    ///
+    /// Proceeds to send block requests to the connected peers, if the ledger is out of date.
+    ///
+    /// While the ledger is on the same canon chain as its peers, blocks are downloaded in parallel
+    /// from every peer that is ahead (see `schedule_block_requests`). To switch to a fork, the block headers
+    /// of the fork are first downloaded and validated, and the blocks are then downloaded as a side chain
+    /// from the single peer that the fork was found with (see `start_side_chain`).
+    ///
+    /// Case 1 - You have at least as much cumulative work as your peer:
     ///     - Do nothing
-    /// Case 2 - You are behind your peer:
+    /// Case 2 - You have less cumulative work than your peer:
     ///     Case 2(a) - `is_fork` is `None`:
     ///         - Peer is being malicious or thinks you are ahead. Both are issues,
     ///           pick a different peer to sync with.
@@ -675,56 +1293,79 @@ impl<N: Network, E: Environment> Ledger<N, E> {
     ///         - Request blocks from your latest state
     ///     Case 2(c) - `is_fork` is `Some(true)`:
     ///             Case 2(c)(a) - Common ancestor is within `ALEO_MAXIMUM_FORK_DEPTH`:
-    ///                  - Revert to common ancestor, and send block requests to sync.
+    ///                  - Request and validate the block headers of the fork, then
+    ///                    download the side chain, and switch to it once it is complete.
     ///             Case 2(c)(b) - Common ancestor is NOT within `ALEO_MAXIMUM_FORK_DEPTH`:
     ///                  Case 2(c)(b)(a) - You can calculate that you are outside of the `ALEO_MAXIMUM_FORK_DEPTH`:
     ///                      - Disconnect from peer.
     ///                  Case 2(c)(b)(b) - You don't know if you are within the `ALEO_MAXIMUM_FORK_DEPTH`:
-    ///                      - Revert to most common ancestor and send block requests to sync.
+    ///                      - Request and validate the block headers of the fork, then
+    ///                        download the side chain, and switch to it once it is complete.
     ///
     async fn update_block_requests(&mut self, peers_router: &PeersRouter<N, E>) {
-        // Ensure the ledger is not awaiting responses from outstanding block requests.
-        if self.number_of_block_requests() > 0 {
+        // If the ledger is awaiting the block headers of a fork, wait for them, unless the request has timed out.
+        if let Some((peer_ip, _, _, timestamp)) = self.block_header_request {
+            if Utc::now().timestamp().saturating_sub(timestamp) < E::BLOCK_REQUEST_TIMEOUT_IN_SECS as i64 {
+                return;
+            }
+            debug!("Block header request to {} has timed out", peer_ip);
+            self.block_header_request = None;
+        }
+
+        // If the ledger is downloading a side chain, continue downloading it.
+        if self.side_chain.is_some() {
+            self.request_side_chain_blocks(peers_router).await;
             return;
         }
 
-        // Acquire the lock for block requests.
-        let _ = self.block_requests_lock.lock();
+        // If the ledger is awaiting responses from outstanding block requests, continue downloading the canon chain.
+        if self.number_of_block_requests() > 0 {
+            self.schedule_block_requests(self.latest_block_height(), peers_router).await;
+            return;
+        }
 
         // Iterate through the peers to check if this node needs to catch up, and determine a peer to sync with.
         // Prioritize the sync nodes before regular peers.
         let mut maximal_peer = None;
         let mut maximal_peer_is_fork = None;
         let mut maximum_block_height = self.latest_block_height();
+        let mut maximum_cumulative_work = match self.canon.latest_cumulative_work() {
+            Ok(cumulative_work) => cumulative_work,
+            Err(error) => {
+                error!("{}", error);
+                return;
+            }
+        };
         let mut maximum_block_locators = Default::default();
 
         // Determine if the peers state has any sync nodes.
-        let sync_nodes: Vec<SocketAddr> = E::SYNC_NODES.iter().map(|ip| ip.parse().unwrap()).collect();
+        let sync_nodes = &self.config.sync_nodes;
         let mut peers_contains_sync_node = false;
         for ip in self.peers_state.keys() {
             peers_contains_sync_node |= sync_nodes.contains(ip);
         }
 
-        // Check if any of the peers are ahead and have a larger block height.
+        // Check if any of the peers are ahead and have a larger cumulative work.
         for (peer_ip, ledger_state) in self.peers_state.iter() {
             // Only update the maximal peer if there are no sync nodes or the peer is a sync node.
             if !peers_contains_sync_node || sync_nodes.contains(peer_ip) {
-                if let Some((is_fork, block_height, block_locators)) = ledger_state {
+                if let Some((is_fork, block_height, cumulative_work, block_locators)) = ledger_state {
                     // Update the maximal peer state if the peer is ahead and the peer knows if you are a fork or not.
                     // This accounts for (Case 1 and Case 2(a))
-                    if *block_height > maximum_block_height && is_fork.is_some() {
+                    if *cumulative_work > maximum_cumulative_work && is_fork.is_some() {
                         maximal_peer = Some(*peer_ip);
                         maximal_peer_is_fork = *is_fork;
                         maximum_block_height = *block_height;
+                        maximum_cumulative_work = *cumulative_work;
                         maximum_block_locators = block_locators.clone();
                     }
                 }
             }
         }
 
-        // Case 1 - Ensure the peer has a higher block height than this ledger.
-        let latest_block_height = self.latest_block_height();
-        if latest_block_height >= maximum_block_height {
+        // Case 1 - Ensure the peer has a larger cumulative work than this ledger.
+        // Note that a fork with a larger cumulative work may have a lower block height than this ledger.
+        if maximal_peer.is_none() {
             return;
         }
 
@@ -737,12 +1378,19 @@ impl<N: Network, E: Environment> Ledger<N, E> {
 
             // Verify the integrity of the block hashes sent by the peer.
             for (block_height, (block_hash, _)) in maximum_block_locators.iter() {
+                // Ensure the block hash does not contradict a checkpoint, as the peer would be on a rejected fork.
+                if !self.canon.checkpoints().is_consistent(*block_height, block_hash) {
+                    warn!("Peer {} contradicts the checkpoint at block {}", peer_ip, block_height);
+                    self.add_misbehaviour(peer_ip, Misbehaviour::InvalidBlockLocators);
+                    return;
+                }
+
                 // Ensure the block hash corresponds with the block height, if the block hash exists in this ledger.
                 if let Ok(expected_block_height) = self.canon.get_block_height(block_hash) {
                     if expected_block_height != *block_height {
                         let error = format!("Invalid block height {} for block hash {}", expected_block_height, block_hash);
                         trace!("{}", error);
-                        self.add_failure(peer_ip, error);
+                        self.add_misbehaviour(peer_ip, Misbehaviour::InvalidBlockLocators);
                         return;
                     } else {
                         // Update the common ancestor, as this block hash exists in this ledger.
@@ -774,27 +1422,24 @@ impl<N: Network, E: Environment> Ledger<N, E> {
             }
 
             // Determine the latest common ancestor.
-            let (latest_common_ancestor, ledger_reverted) =
+            let latest_common_ancestor =
                 // Case 2(b) - This ledger is not a fork of the peer, it is on the same canon chain.
                 if !is_fork {
                     // Continue to sync from the latest block height of this ledger, if the peer is honest.
                     match first_deviating_locator.is_none() {
-                        true => (maximum_common_ancestor, false),
-                        false => (latest_block_height, false),
+                        true => maximum_common_ancestor,
+                        false => latest_block_height,
                     }
                 }
                 // Case 2(c) - This ledger is on a fork of the peer.
                 else {
                     // Case 2(c)(a) - If the common ancestor is within the fork range of this ledger,
-                    // proceed to switch to the fork.
+                    // proceed to switch to the fork, once its block headers are validated.
                     if latest_block_height.saturating_sub(maximum_common_ancestor) <= N::ALEO_MAXIMUM_FORK_DEPTH
                     {
                         info!("Found a longer chain from {} starting at block {}", peer_ip, maximum_common_ancestor);
-                        // If the latest block is the same as the maximum common ancestor, do not revert.
-                        if latest_block_height != maximum_common_ancestor && !self.revert_to_block_height(maximum_common_ancestor) {
-                            return;
-                        }
-                        (maximum_common_ancestor, true)
+                        self.request_block_headers(peer_ip, maximum_common_ancestor, maximum_block_height, peers_router).await;
+                        return;
                     }
                     // Case 2(c)(b) - If the common ancestor is NOT within `ALEO_MAXIMUM_FORK_DEPTH`.
                     else
@@ -817,49 +1462,383 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                             return;
                         }
                         // Case 2(c)(b)(b) - You don't know if your real common ancestor is within `ALEO_MAXIMUM_FORK_DEPTH`.
-                        // Revert to the common ancestor anyways.
+                        // Switch to the fork from the common ancestor anyways, once its block headers are validated.
                         else {
                             info!("Found a potentially longer chain from {} starting at block {}", peer_ip, maximum_common_ancestor);
-                            match self.revert_to_block_height(maximum_common_ancestor) {
-                                true => (maximum_common_ancestor, true),
-                                false => return
-                            }
+                            self.request_block_headers(peer_ip, maximum_common_ancestor, maximum_block_height, peers_router).await;
+                            return;
                         }
                     }
                 };
 
-            // TODO (howardwu): Ensure the start <= end.
-            // Determine the start and end block heights to request.
-            let number_of_block_requests = std::cmp::min(maximum_block_height - latest_common_ancestor, E::MAXIMUM_BLOCK_REQUEST);
-            let start_block_height = latest_common_ancestor + 1;
-            let end_block_height = start_block_height + number_of_block_requests - 1;
+            // As this ledger is on the same canon chain as the peer, download the blocks in parallel from every peer that is ahead.
+            self.schedule_block_requests(latest_common_ancestor, peers_router).await;
+        }
+    }
+
+    ///
+    /// Requests the block headers of a fork from the given peer, starting after the given block height,
+    /// which is either the common ancestor or the latest block height of the side chain being extended.
+    ///
+    /// The ledger only downloads the blocks of the fork once the block headers
+    /// are received and validated (see `start_side_chain`).
+    ///
+    async fn request_block_headers(
+        &mut self,
+        peer_ip: SocketAddr,
+        block_height: u32,
+        peer_block_height: u32,
+        peers_router: &PeersRouter<N, E>,
+    ) {
+        // Ensure the peer has blocks after the given block height.
+        if peer_block_height <= block_height {
+            return;
+        }
+
+        let start_block_height = block_height + 1;
+        let end_block_height = std::cmp::min(peer_block_height, block_height + E::MAXIMUM_BLOCK_HEADER_REQUEST);
+        debug!(
+            "Request block headers {} to {} from {}",
+            start_block_height, end_block_height, peer_ip
+        );
+
+        // Send a `BlockHeaderRequest` message to the peer.
+        let request = PeersRequest::MessageSend(peer_ip, Message::BlockHeaderRequest(start_block_height, end_block_height));
+        if let Err(error) = peers_router.send(request).await {
+            warn!("[BlockHeaderRequest] {}", error);
+            return;
+        }
+        self.block_header_request = Some((peer_ip, start_block_height, end_block_height, Utc::now().timestamp()));
+    }
+
+    ///
+    /// Starts to download the side chain of the given peer, if the given block headers form a valid chain
+    /// after the given block height with a larger cumulative work than the canon chain.
+    ///
+    /// The block headers either start a new side chain after the common ancestor, or extend the side chain
+    /// of the peer. While the side chain does not have a larger cumulative work than the canon chain,
+    /// it is extended with the next block headers of the peer, up to `ALEO_MAXIMUM_FORK_DEPTH` blocks.
+    ///
+    /// The canon chain is left unchanged while the side chain is downloaded, and the ledger only switches
+    /// to the side chain once all of its blocks are downloaded (see `switch_to_side_chain`).
+    ///
+    async fn start_side_chain(
+        &mut self,
+        peer_ip: SocketAddr,
+        block_height: u32,
+        block_headers: Vec<(N::BlockHash, BlockHeader<N>)>,
+        peers_router: &PeersRouter<N, E>,
+    ) {
+        // Extend the side chain of the peer, if the block headers follow it, or start a new side chain from the common ancestor.
+        let mut side_chain = match self.side_chain.take() {
+            Some(side_chain) if side_chain.peer_ip() == peer_ip && side_chain.latest_block_height() == block_height => side_chain,
+            _ => match (
+                self.canon.get_block_header(block_height),
+                self.canon.get_cumulative_work(block_height),
+            ) {
+                (Ok(block_header), Ok(cumulative_work)) => SideChain::new(peer_ip, block_height, block_header, cumulative_work),
+                (Err(error), _) | (_, Err(error)) => {
+                    error!("{}", error);
+                    return;
+                }
+            },
+        };
+
+        // Ensure the block headers form a valid chain from the side chain, and determine its cumulative work.
+        let cumulative_work =
+            match self
+                .canon
+                .check_block_headers_after(side_chain.latest_block_header(), side_chain.cumulative_work(), &block_headers)
+            {
+                Ok(cumulative_work) => cumulative_work,
+                Err(error) => {
+                    warn!("Invalid block headers from {}: {}", peer_ip, error);
+                    self.add_misbehaviour(peer_ip, Misbehaviour::InvalidBlockHeaders);
+                    return;
+                }
+            };
+        side_chain.extend(block_headers, cumulative_work);
+
+        // Retrieve the cumulative work of the canon chain, and the latest block height of the peer.
+        let latest_cumulative_work = match self.canon.latest_cumulative_work() {
+            Ok(cumulative_work) => cumulative_work,
+            Err(error) => {
+                error!("{}", error);
+                return;
+            }
+        };
+        let peer_block_height = match self.peers_state.get(&peer_ip) {
+            Some(Some((_, block_height, _, _))) => *block_height,
+            _ => 0,
+        };
+
+        // If the side chain has a larger cumulative work than the canon chain, download its blocks.
+        if side_chain.cumulative_work() > latest_cumulative_work {
+            info!(
+                "Downloading the side chain from {} starting at block {}",
+                peer_ip,
+                side_chain.common_ancestor()
+            );
+            self.side_chain = Some(side_chain);
+            self.request_side_chain_blocks(peers_router).await;
+        }
+        // Otherwise, if the peer has further block headers within the maximum fork depth, extend the side chain.
+        else if side_chain.latest_block_height() < peer_block_height && side_chain.number_of_blocks() < N::ALEO_MAXIMUM_FORK_DEPTH {
+            let latest_block_height = side_chain.latest_block_height();
+            self.side_chain = Some(side_chain);
+            self.request_block_headers(peer_ip, latest_block_height, peer_block_height, peers_router)
+                .await;
+        } else {
+            debug!("The fork from {} does not have more cumulative work than the canon chain", peer_ip);
+        }
+    }
+
+    ///
+    /// Requests the next missing blocks of the side chain from its peer, if the peer has no outstanding block requests.
+    ///
+    /// If the side chain no longer has a larger cumulative work than the canon chain, or if the block requests
+    /// to the peer have recently timed out, the side chain is dropped.
+    ///
+    async fn request_side_chain_blocks(&mut self, peers_router: &PeersRouter<N, E>) {
+        let (peer_ip, cumulative_work, missing_blocks) = match &self.side_chain {
+            Some(side_chain) => (side_chain.peer_ip(), side_chain.cumulative_work(), side_chain.missing_blocks()),
+            None => return,
+        };
+
+        // Ensure the side chain has a larger cumulative work than the canon chain. This is not the case
+        // if the block headers to extend the side chain were not received.
+        match self.canon.latest_cumulative_work() {
+            Ok(latest_cumulative_work) if cumulative_work > latest_cumulative_work => (),
+            Ok(_) => {
+                debug!("Dropping the side chain from {}, as it does not have more cumulative work", peer_ip);
+                self.side_chain = None;
+                return;
+            }
+            Err(error) => {
+                error!("{}", error);
+                return;
+            }
+        }
+
+        // Ensure the peer is still providing the side chain.
+        if self.has_recent_block_request_timeout(peer_ip) {
+            debug!("Dropping the side chain from {}, as its block requests timed out", peer_ip);
+            self.side_chain = None;
+            return;
+        }
+        // Ensure the peer has no outstanding block requests.
+        match self.block_requests.get(&peer_ip) {
+            Some(requests) if requests.is_empty() => (),
+            _ => return,
+        }
+
+        // Determine the next consecutive range of missing blocks to request.
+        let blocks_to_request: Vec<(u32, N::BlockHash)> = missing_blocks
+            .iter()
+            .enumerate()
+            .take_while(|(index, (block_height, _))| *block_height == missing_blocks[0].0 + *index as u32)
+            .map(|(_, missing_block)| *missing_block)
+            .take(self.config.maximum_block_request as usize)
+            .collect();
+        let (start_block_height, end_block_height) = match (blocks_to_request.first(), blocks_to_request.last()) {
+            (Some((start_block_height, _)), Some((end_block_height, _))) => (*start_block_height, *end_block_height),
+            _ => return,
+        };
+        debug!("Request blocks {} to {} from {}", start_block_height, end_block_height, peer_ip);
+
+        // Send a `BlockRequest` message to the peer.
+        let request = PeersRequest::MessageSend(peer_ip, Message::BlockRequest(start_block_height, end_block_height));
+        if let Err(error) = peers_router.send(request).await {
+            warn!("[BlockRequest] {}", error);
+            return;
+        }
+
+        // Log each block request to ensure the peer responds with all requested blocks.
+        // Include the expected block hash from the side chain, as it differs from the canon chain.
+        for (block_height, block_hash) in blocks_to_request {
+            self.add_block_request(peer_ip, block_height, Some(block_hash));
+        }
+    }
+
+    ///
+    /// Adds the given block to the side chain, and switches to the side chain once all of its blocks are downloaded.
+    /// Returns `true` if the ledger switched to the side chain.
+    ///
+    /// The blocks are only verified once, as the ledger switches to the side chain.
+    ///
+    fn add_side_chain_block(&mut self, block: Block<N>) -> bool {
+        if let Some(side_chain) = &mut self.side_chain {
+            side_chain.add_block(block);
+            if side_chain.is_complete() {
+                return self.switch_to_side_chain();
+            }
+        }
+        false
+    }
+
+    ///
+    /// Switches the canon chain to the downloaded side chain, if it has a larger cumulative work than the canon chain.
+    ///
+    /// The switch is atomic: if any block of the side chain is invalid, the canon chain is left unchanged,
+    /// and the peer is penalized. Returns `true` if the ledger switched to the side chain.
+    ///
+    fn switch_to_side_chain(&mut self) -> bool {
+        let side_chain = match self.side_chain.take() {
+            Some(side_chain) => side_chain,
+            None => return false,
+        };
+        let (peer_ip, common_ancestor) = (side_chain.peer_ip(), side_chain.common_ancestor());
+
+        // Ensure the side chain still has a larger cumulative work than the canon chain,
+        // so that a failure to switch to it is due to an invalid block of the side chain.
+        match self.canon.latest_cumulative_work() {
+            Ok(latest_cumulative_work) if side_chain.cumulative_work() > latest_cumulative_work => (),
+            Ok(_) => {
+                debug!("The side chain from {} no longer has more cumulative work", peer_ip);
+                return false;
+            }
+            Err(error) => {
+                error!("{}", error);
+                return false;
+            }
+        }
+        let blocks = side_chain.into_blocks();
+
+        match self.canon.switch_to_branch(common_ancestor, &blocks) {
+            Ok(removed_blocks) => {
+                let latest_block_height = self.latest_block_height();
+                info!("Switched to the side chain from {} at block {}", peer_ip, latest_block_height);
+
+                // Update the last block update timestamp.
+                self.last_block_update_timestamp = Instant::now();
+                // Set the terminator bit to `true` to ensure the miner resets state.
+                self.terminator.store(true, Ordering::SeqCst);
+
+                // Mark the removed blocks as orphaned, and the added blocks as canonical, if they were mined by this node.
+                for removed_block in &removed_blocks {
+                    if let Err(error) = self.mining_history.set_orphaned(&removed_block.hash()) {
+                        warn!("Failed to update the mining history: {}", error);
+                    }
+                }
+                for block in &blocks {
+                    if self.unconfirmed_blocks.contains_key(&block.previous_block_hash()) {
+                        self.unconfirmed_blocks.remove(&block.previous_block_hash());
+                    }
+                    if let Err(error) = self.mining_history.set_canonical(block.height(), &block.hash()) {
+                        warn!("Failed to update the mining history: {}", error);
+                    }
+                    // Filter the memory pool of the transactions in the side chain, and of any transactions they conflict with.
+                    self.memory_pool.remove_transactions(block.transactions());
+                }
+                // Evict the expired transactions from the memory pool.
+                for transaction_id in self.memory_pool.remove_expired(latest_block_height) {
+                    debug!("Evicted expired transaction {} from the memory pool", transaction_id);
+                }
+
+                // Return the transactions of the removed blocks to the memory pool.
+                self.reinject_transactions(&removed_blocks);
+                true
+            }
+            Err(error) => {
+                warn!("Failed to switch to the side chain from {}: {}", peer_ip, error);
+                self.add_misbehaviour(peer_ip, Misbehaviour::InvalidBlock);
+                false
+            }
+        }
+    }
+
+    ///
+    /// Splits the missing blocks after the given block height into ranges, and requests each range
+    /// from a different idle peer that is ahead of the range, so that blocks are downloaded in parallel.
+    ///
+    /// A peer is idle if it has no outstanding block requests, and has not recently timed out on a block request.
+    /// Blocks are only requested up to `MAXIMUM_UNCONFIRMED_BLOCKS` ahead of the given block height,
+    /// as blocks received out of order are buffered in the unconfirmed blocks until the ledger catches up.
+    ///
+    async fn schedule_block_requests(&mut self, block_height: u32, peers_router: &PeersRouter<N, E>) {
+        // Determine if the peers state has any sync nodes, to prioritize them before regular peers.
+        let sync_nodes = &self.config.sync_nodes;
+        let peers_contains_sync_node = self.peers_state.keys().any(|ip| sync_nodes.contains(ip));
+
+        // Determine the idle peers on the same canon chain that are ahead of this ledger.
+        let mut idle_peers: Vec<(SocketAddr, u32)> = self
+            .peers_state
+            .iter()
+            .filter(|(peer_ip, _)| !peers_contains_sync_node || sync_nodes.contains(peer_ip))
+            .filter(|(peer_ip, _)| {
+                self.block_requests
+                    .get(peer_ip)
+                    .map(|requests| requests.is_empty())
+                    .unwrap_or(false)
+            })
+            .filter(|(peer_ip, _)| !self.has_recent_block_request_timeout(**peer_ip))
+            .filter_map(|(peer_ip, ledger_state)| match ledger_state {
+                Some((Some(false), peer_block_height, _, _)) if *peer_block_height > block_height => Some((*peer_ip, *peer_block_height)),
+                _ => None,
+            })
+            .collect();
+        if idle_peers.is_empty() {
+            return;
+        }
+        // Sort the idle peers from the lowest to the highest block height, so that each range is requested
+        // from the peer that is least ahead of it, and the peers furthest ahead remain for the later ranges.
+        idle_peers.sort_by_key(|(_, peer_block_height)| *peer_block_height);
+
+        // Determine the block heights that are already requested, or already downloaded.
+        let pending_block_heights: HashSet<u32> = self
+            .block_requests
+            .values()
+            .flat_map(|requests| requests.keys().map(|(block_height, _)| *block_height))
+            .chain(self.unconfirmed_blocks.values().map(|block| block.height()))
+            .collect();
+
+        // Determine the missing block heights within the download window.
+        let maximum_block_height = idle_peers
+            .iter()
+            .map(|(_, peer_block_height)| *peer_block_height)
+            .max()
+            .unwrap_or(block_height);
+        let end_of_window = std::cmp::min(maximum_block_height, block_height.saturating_add(MAXIMUM_UNCONFIRMED_BLOCKS));
+        let missing_block_heights: Vec<u32> = (block_height + 1..=end_of_window)
+            .filter(|block_height| !pending_block_heights.contains(block_height))
+            .collect();
+
+        // Split the missing block heights into contiguous ranges, sized to spread them evenly across the idle peers.
+        let range_size = (missing_block_heights.len() + idle_peers.len() - 1) / idle_peers.len();
+        let range_size = std::cmp::max(1, std::cmp::min(range_size, self.config.maximum_block_request as usize));
+        let mut ranges: Vec<(u32, u32)> = Vec::new();
+        for block_height in missing_block_heights {
+            match ranges.last_mut() {
+                Some((start, end)) if *end + 1 == block_height && ((block_height - *start) as usize) < range_size => *end = block_height,
+                _ => ranges.push((block_height, block_height)),
+            }
+        }
+
+        // Request each range from an idle peer that is ahead of it.
+        for (start_block_height, end_block_height) in ranges {
+            let peer_ip = match idle_peers
+                .iter()
+                .position(|(_, peer_block_height)| *peer_block_height >= end_block_height)
+            {
+                Some(index) => idle_peers.remove(index).0,
+                None => continue,
+            };
             debug!("Request blocks {} to {} from {}", start_block_height, end_block_height, peer_ip);
 
             // Send a `BlockRequest` message to the peer.
             let request = PeersRequest::MessageSend(peer_ip, Message::BlockRequest(start_block_height, end_block_height));
             if let Err(error) = peers_router.send(request).await {
                 warn!("[BlockRequest] {}", error);
-                return;
+                continue;
             }
 
             // Log each block request to ensure the peer responds with all requested blocks.
             for block_height in start_block_height..=end_block_height {
-                // If the ledger was reverted, include the expected new block hash for the fork.
-                match ledger_reverted {
-                    true => self.add_block_request(peer_ip, block_height, maximum_block_locators.get_block_hash(block_height)),
-                    false => self.add_block_request(peer_ip, block_height, None),
-                };
+                self.add_block_request(peer_ip, block_height, None);
             }
         }
     }
 
-    ///
-    /// Returns the number of outstanding block requests.
-    ///
-    fn number_of_block_requests(&self) -> usize {
-        self.block_requests.values().map(|r| r.len()).sum()
-    }
-
     ///
     /// Adds a block request for the given block height to the specified peer.
     ///
@@ -869,9 +1848,9 @@ impl<N: Network, E: Environment> Ledger<N, E> {
             match self.block_requests.get_mut(&peer_ip) {
                 Some(requests) => match requests.insert((block_height, block_hash), Utc::now().timestamp()) {
                     None => debug!("Requesting block {} from {}", block_height, peer_ip),
-                    Some(_old_request) => self.add_failure(peer_ip, format!("Duplicate block request for {}", peer_ip)),
+                    Some(_old_request) => warn!("Duplicate block request for {}", peer_ip),
                 },
-                None => self.add_failure(peer_ip, format!("Missing block requests for {}", peer_ip)),
+                None => warn!("Missing block requests for {}", peer_ip),
             };
         }
     }
@@ -893,7 +1872,13 @@ impl<N: Network, E: Environment> Ledger<N, E> {
     fn remove_block_request(&mut self, peer_ip: SocketAddr, block_height: u32, block_hash: N::BlockHash) -> bool {
         // Ensure the block height corresponds to a requested block.
         if !self.contains_block_request(peer_ip, block_height, Some(block_hash)) {
-            self.add_failure(peer_ip, "Received an invalid block response".to_string());
+            // If the block request recently timed out and was re-assigned, the peer is slow, rather than misbehaving.
+            if self.has_recent_block_request_timeout(peer_ip) {
+                trace!("Received a timed out block {} from {}", block_height, peer_ip);
+                return false;
+            }
+            warn!("Received an unrequested block {} from {}", block_height, peer_ip);
+            self.add_misbehaviour(peer_ip, Misbehaviour::UnrequestedBlockResponse);
             false
         } else {
             if let Some(requests) = self.block_requests.get_mut(&peer_ip) {
@@ -901,7 +1886,7 @@ impl<N: Network, E: Environment> Ledger<N, E> {
                     requests.remove(&(block_height, Some(block_hash))).is_some() || requests.remove(&(block_height, None)).is_some();
                 match is_success {
                     true => return true,
-                    false => self.add_failure(peer_ip, format!("Non-existent block request from {}", peer_ip)),
+                    false => warn!("Non-existent block request from {}", peer_ip),
                 }
             }
             false
@@ -909,51 +1894,139 @@ impl<N: Network, E: Environment> Ledger<N, E> {
     }
 
     ///
-    /// Removes block requests that have expired.
+    /// Returns `true` if a block request to the specified peer has timed out within the last `E::RADIO_SILENCE_IN_SECS`.
     ///
-    fn remove_expired_block_requests(&mut self) {
-        // Clear all block requests that have lived longer than `E::RADIO_SILENCE_IN_SECS`.
-        let now = Utc::now().timestamp();
-        self.block_requests.iter_mut().for_each(|(_peer, block_requests)| {
-            block_requests.retain(|_, time_of_request| now.saturating_sub(*time_of_request) < E::RADIO_SILENCE_IN_SECS as i64)
-        });
+    fn has_recent_block_request_timeout(&self, peer_ip: SocketAddr) -> bool {
+        match self.block_request_timeouts.get(&peer_ip) {
+            Some(timestamp) => Utc::now().timestamp().saturating_sub(*timestamp) < E::RADIO_SILENCE_IN_SECS as i64,
+            None => false,
+        }
     }
 
     ///
-    /// Adds the given failure message to the specified peer IP.
+    /// Removes block requests that have expired.
     ///
-    fn add_failure(&mut self, peer_ip: SocketAddr, failure: String) {
-        trace!("Adding failure for {}: {}", peer_ip, failure);
-        match self.failures.get_mut(&peer_ip) {
-            Some(failures) => failures.push((failure, Utc::now().timestamp())),
-            None => error!("Missing failure entry for {}", peer_ip),
-        };
+    /// If any block request to a peer has lived longer than `E::BLOCK_REQUEST_TIMEOUT_IN_SECS`,
+    /// all of its block requests are cleared, so they may be re-assigned to other peers.
+    ///
+    fn remove_expired_block_requests(&mut self) {
+        let now = Utc::now().timestamp();
+        for (peer_ip, block_requests) in self.block_requests.iter_mut() {
+            let is_expired = block_requests
+                .values()
+                .any(|time_of_request| now.saturating_sub(*time_of_request) >= E::BLOCK_REQUEST_TIMEOUT_IN_SECS as i64);
+            if is_expired {
+                debug!("Block requests to {} have timed out", peer_ip);
+                block_requests.clear();
+                self.block_request_timeouts.insert(*peer_ip, now);
+            }
+        }
     }
 
     ///
-    /// Removes failures that have expired.
+    /// Penalizes the specified peer IP for the given misbehaviour in the peer book.
     ///
-    fn remove_expired_failures(&mut self) {
-        // Clear all failures that have lived longer than `E::FAILURE_EXPIRY_TIME_IN_SECS`.
-        let now = Utc::now().timestamp();
-        self.failures.iter_mut().for_each(|(_, failures)| {
-            failures.retain(|(_, time_of_fail)| now.saturating_sub(*time_of_fail) < E::FAILURE_EXPIRY_TIME_IN_SECS as i64)
-        });
+    fn add_misbehaviour(&self, peer_ip: SocketAddr, misbehaviour: Misbehaviour) {
+        trace!("Adding misbehaviour for {}: {:?}", peer_ip, misbehaviour);
+        match self.peer_book.add_misbehaviour::<E>(peer_ip, misbehaviour) {
+            Ok(true) => warn!("Banning {} for misbehaviour ({:?})", peer_ip, misbehaviour),
+            Ok(false) => (),
+            Err(error) => warn!("[PeerBook] {}", error),
+        }
     }
 
     ///
-    /// Disconnects from connected peers who exhibit frequent failures.
+    /// Disconnects from connected peers who have been banned.
     ///
-    async fn disconnect_from_failing_peers(&self, ledger_router: &LedgerRouter<N, E>) {
-        let peers_to_disconnect = self
-            .failures
-            .iter()
-            .filter(|(_, failures)| failures.len() > E::MAXIMUM_NUMBER_OF_FAILURES)
-            .map(|(peer_ip, _)| peer_ip);
+    async fn disconnect_from_banned_peers(&self, peers_router: &PeersRouter<N, E>, ledger_router: &LedgerRouter<N, E>) {
+        let peers_to_disconnect = self.peers_state.keys().filter(|peer_ip| self.peer_book.is_banned(peer_ip.ip()));
         for peer_ip in peers_to_disconnect {
+            // Send a `Disconnect` message to the peer.
+            let request = PeersRequest::MessageSend(*peer_ip, Message::Disconnect);
+            if let Err(error) = peers_router.send(request).await {
+                warn!("[Disconnect] {}", error);
+            }
+            // Remove the peer from the ledger.
             if let Err(error) = ledger_router.send(LedgerRequest::Disconnect(*peer_ip)).await {
-                warn!("Failed to send disconnect message to failing peer {}: {}", peer_ip, error);
+                warn!("Failed to send disconnect message to banned peer {}: {}", peer_ip, error);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+    use snarkos_ledger::storage::rocksdb::RocksDB;
+    use snarkvm::dpc::testnet2::Testnet2;
+
+    type N = Testnet2;
+    type E = Client<Testnet2>;
+
+    fn temp_dir() -> std::path::PathBuf {
+        tempfile::tempdir().expect("Failed to open temporary directory").into_path()
+    }
+
+    /// Returns a new ledger, with its storage in temporary directories.
+    fn new_ledger() -> Ledger<N, E> {
+        let peer_book = PeerBook::open::<RocksDB, _>(temp_dir()).expect("Failed to open the peer book");
+        let mining_history = MiningHistory::open::<RocksDB, _>(temp_dir()).expect("Failed to open the mining history");
+        Ledger::open::<RocksDB, _>(temp_dir(), Config::new::<E>(), peer_book, mining_history).expect("Failed to open the ledger")
+    }
+
+    /// Mines the given number of blocks, and adds them to the given ledger state.
+    fn mine_blocks(canon: &mut LedgerState<N>, number_of_blocks: usize) -> Vec<Block<N>> {
+        let rng = &mut thread_rng();
+        let terminator = AtomicBool::new(false);
+        let address = Account::<N>::new(rng).address();
+
+        let mut blocks = Vec::with_capacity(number_of_blocks);
+        for _ in 0..number_of_blocks {
+            let block = canon.mine_next_block(address, &[], &terminator, rng).expect("Failed to mine");
+            canon.add_next_block(&block).expect("Failed to add next block to ledger");
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    #[tokio::test]
+    async fn test_switch_to_side_chain() {
+        let (peers_router, mut peers_handler) = mpsc::channel(1024);
+        let (ledger_router, _ledger_handler) = mpsc::channel(1024);
+        let peer_ip: SocketAddr = "127.0.0.1:4132".parse().unwrap();
+
+        // Mine one block on the ledger, and two blocks on the fork of the peer.
+        let mut ledger = new_ledger();
+        let canon_blocks = mine_blocks(&mut ledger.canon, 1);
+        let mut fork = LedgerState::<N>::open::<RocksDB, _>(temp_dir(), false).expect("Failed to initialize ledger");
+        let blocks = mine_blocks(&mut fork, 2);
+
+        // Receive the block headers of the fork, as requested from the peer.
+        ledger.block_requests.insert(peer_ip, Default::default());
+        ledger.block_header_request = Some((peer_ip, 1, 2, Utc::now().timestamp()));
+        let block_headers = blocks.iter().map(|block| (block.hash(), block.header().clone())).collect();
+        let request = LedgerRequest::BlockHeaderResponse(peer_ip, block_headers);
+        ledger.update(request, &peers_router, &ledger_router).await;
+
+        // Ensure the blocks of the side chain are requested from the peer, and the canon chain is unchanged.
+        match peers_handler.try_recv() {
+            Ok(PeersRequest::MessageSend(ip, Message::BlockRequest(1, 2))) => assert_eq!(peer_ip, ip),
+            request => panic!("Expected a block request for the side chain, found {:?}", request),
+        }
+        assert!(ledger.side_chain.is_some());
+        assert_eq!(canon_blocks[0].hash(), ledger.canon.latest_block_hash());
+
+        // Ensure the ledger only switches to the side chain once all of its blocks are received.
+        let request = LedgerRequest::BlockResponse(peer_ip, blocks[1].clone());
+        ledger.update(request, &peers_router, &ledger_router).await;
+        assert_eq!(canon_blocks[0].hash(), ledger.canon.latest_block_hash());
+
+        let request = LedgerRequest::BlockResponse(peer_ip, blocks[0].clone());
+        ledger.update(request, &peers_router, &ledger_router).await;
+        assert_eq!(blocks[1].hash(), ledger.canon.latest_block_hash());
+        let fork_cumulative_work = fork.latest_cumulative_work().unwrap();
+        assert_eq!(fork_cumulative_work, ledger.canon.latest_cumulative_work().unwrap());
+        assert!(ledger.side_chain.is_none());
+    }
+}
@@ -0,0 +1,464 @@
+// Copyright (C) 2019-2021 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::TransactionError;
+use snarkvm::{dpc::prelude::*, utilities::ToBytes};
+
+use std::{cmp::Ordering, collections::HashMap};
+
+///
+/// An unconfirmed transaction held in the memory pool.
+///
+#[derive(Clone, Debug)]
+struct PooledTransaction<N: Network> {
+    /// The unconfirmed transaction.
+    transaction: Transaction<N>,
+    /// The serialized size of the transaction in bytes.
+    size_in_bytes: usize,
+    /// The fee paid by the transaction, as given by its (non-negative) value balance.
+    fee: u64,
+    /// The latest block height of the canon chain when the transaction entered the memory pool.
+    block_height: u32,
+}
+
+impl<N: Network> PooledTransaction<N> {
+    ///
+    /// Returns the ordering of the fee-per-byte of `self` relative to `other`.
+    ///
+    fn cmp_fee_per_byte(&self, other: &Self) -> Ordering {
+        // Compare `fee / size_in_bytes` by cross-multiplying, to avoid rounding.
+        let lhs = self.fee as u128 * other.size_in_bytes as u128;
+        let rhs = other.fee as u128 * self.size_in_bytes as u128;
+        lhs.cmp(&rhs)
+    }
+}
+
+///
+/// A pool of unconfirmed transactions, bounded in count and size, with priority given by fee-per-byte.
+///
+#[derive(Clone, Debug)]
+pub struct MemoryPool<N: Network> {
+    /// The map of transaction IDs to their unconfirmed transactions.
+    transactions: HashMap<N::TransactionID, PooledTransaction<N>>,
+    /// The map of serial numbers to the ID of the unconfirmed transaction that spends them.
+    serial_numbers: HashMap<N::SerialNumber, N::TransactionID>,
+    /// The map of commitments to the ID of the unconfirmed transaction that creates them.
+    commitments: HashMap<N::Commitment, N::TransactionID>,
+    /// The total serialized size of the unconfirmed transactions in bytes.
+    size_in_bytes: usize,
+    /// The maximum number of unconfirmed transactions.
+    maximum_transactions: usize,
+    /// The maximum total serialized size of the unconfirmed transactions in bytes.
+    maximum_size_in_bytes: usize,
+    /// The number of blocks after which an unconfirmed transaction expires.
+    expiry_in_blocks: u32,
+}
+
+impl<N: Network> MemoryPool<N> {
+    ///
+    /// Initializes a new instance of the memory pool, with the given limits.
+    ///
+    pub fn new(maximum_transactions: usize, maximum_size_in_bytes: usize, expiry_in_blocks: u32) -> Self {
+        Self {
+            transactions: Default::default(),
+            serial_numbers: Default::default(),
+            commitments: Default::default(),
+            size_in_bytes: 0,
+            maximum_transactions,
+            maximum_size_in_bytes,
+            expiry_in_blocks,
+        }
+    }
+
+    ///
+    /// Returns the number of unconfirmed transactions.
+    ///
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    ///
+    /// Returns `true` if there are no unconfirmed transactions.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    ///
+    /// Returns the total serialized size of the unconfirmed transactions in bytes.
+    ///
+    pub fn size_in_bytes(&self) -> usize {
+        self.size_in_bytes
+    }
+
+    ///
+    /// Returns `true` if the given transaction ID exists in the memory pool.
+    ///
+    pub fn contains_transaction(&self, transaction_id: &N::TransactionID) -> bool {
+        self.transactions.contains_key(transaction_id)
+    }
+
+    ///
+    /// Returns `true` if the given serial number is spent by an unconfirmed transaction.
+    ///
+    pub fn contains_serial_number(&self, serial_number: &N::SerialNumber) -> bool {
+        self.serial_numbers.contains_key(serial_number)
+    }
+
+    ///
+    /// Returns `true` if the given commitment is created by an unconfirmed transaction.
+    ///
+    pub fn contains_commitment(&self, commitment: &N::Commitment) -> bool {
+        self.commitments.contains_key(commitment)
+    }
+
+    ///
+    /// Returns the unconfirmed transaction for the given transaction ID, if it exists.
+    ///
+    pub fn get_transaction(&self, transaction_id: &N::TransactionID) -> Option<&Transaction<N>> {
+        self.transactions.get(transaction_id).map(|pooled| &pooled.transaction)
+    }
+
+    ///
+    /// Returns the unconfirmed transactions, from the highest to the lowest fee-per-byte.
+    ///
+    pub fn transactions(&self) -> Vec<Transaction<N>> {
+        let mut pooled: Vec<&PooledTransaction<N>> = self.transactions.values().collect();
+        pooled.sort_by(|a, b| b.cmp_fee_per_byte(a));
+        pooled.into_iter().map(|pooled| pooled.transaction.clone()).collect()
+    }
+
+    ///
+    /// Adds the given transaction to the memory pool, at the given latest block height.
+    ///
+    /// If the memory pool is full, unconfirmed transactions with a lower fee-per-byte are evicted
+    /// to make room for the given transaction, and their transaction IDs are returned.
+    ///
+    pub fn add_transaction(&mut self, transaction: &Transaction<N>, block_height: u32) -> Result<Vec<N::TransactionID>, TransactionError> {
+        let size_in_bytes = transaction
+            .to_bytes_le()
+            .map_err(|error| TransactionError::Invalid(format!("{} ({})", transaction.transaction_id(), error)))?
+            .len();
+
+        self.add_pooled_transaction(PooledTransaction {
+            transaction: transaction.clone(),
+            size_in_bytes,
+            fee: transaction.value_balance().0.max(0) as u64,
+            block_height,
+        })
+    }
+
+    ///
+    /// Adds the given pooled transaction to the memory pool, evicting unconfirmed transactions
+    /// with a lower fee-per-byte if the memory pool is full.
+    ///
+    fn add_pooled_transaction(&mut self, pooled: PooledTransaction<N>) -> Result<Vec<N::TransactionID>, TransactionError> {
+        let transaction = &pooled.transaction;
+        let transaction_id = transaction.transaction_id();
+
+        // Ensure the transaction does not already exist in the memory pool.
+        if self.contains_transaction(&transaction_id) {
+            return Err(TransactionError::AlreadyInMemoryPool(transaction_id.to_string()));
+        }
+        // Ensure the serial numbers are not already spent by an unconfirmed transaction.
+        if let Some(serial_number) = transaction
+            .serial_numbers()
+            .find(|serial_number| self.contains_serial_number(serial_number))
+        {
+            return Err(TransactionError::ConflictingSerialNumber(serial_number.to_string()));
+        }
+        // Ensure the commitments are not already created by an unconfirmed transaction.
+        if let Some(commitment) = transaction.commitments().find(|commitment| self.contains_commitment(commitment)) {
+            return Err(TransactionError::ConflictingCommitment(commitment.to_string()));
+        }
+
+        // Ensure the transaction fits in the memory pool on its own.
+        if pooled.size_in_bytes > self.maximum_size_in_bytes || self.maximum_transactions == 0 {
+            return Err(TransactionError::Rejected(format!(
+                "transaction {} exceeds the memory pool limits",
+                transaction_id
+            )));
+        }
+
+        // Select the unconfirmed transactions to evict, from the lowest fee-per-byte, until the transaction fits.
+        let mut candidates: Vec<&PooledTransaction<N>> = self.transactions.values().collect();
+        candidates.sort_by(|a, b| a.cmp_fee_per_byte(b));
+
+        let mut evicted = Vec::new();
+        let (mut number_of_transactions, mut size_in_bytes) = (self.transactions.len(), self.size_in_bytes);
+        let mut candidates = candidates.into_iter();
+        while number_of_transactions + 1 > self.maximum_transactions || size_in_bytes + pooled.size_in_bytes > self.maximum_size_in_bytes {
+            match candidates.next() {
+                // Only evict unconfirmed transactions that pay a strictly lower fee-per-byte.
+                Some(candidate) if candidate.cmp_fee_per_byte(&pooled) == Ordering::Less => {
+                    evicted.push(candidate.transaction.transaction_id());
+                    number_of_transactions -= 1;
+                    size_in_bytes -= candidate.size_in_bytes;
+                }
+                _ => {
+                    return Err(TransactionError::Rejected(format!(
+                        "the memory pool is full, and transaction {} has an insufficient fee",
+                        transaction_id
+                    )));
+                }
+            }
+        }
+
+        // Evict the selected unconfirmed transactions.
+        for transaction_id in &evicted {
+            self.remove_transaction(transaction_id);
+        }
+
+        // Add the transaction to the memory pool.
+        for serial_number in transaction.serial_numbers() {
+            self.serial_numbers.insert(*serial_number, transaction_id);
+        }
+        for commitment in transaction.commitments() {
+            self.commitments.insert(*commitment, transaction_id);
+        }
+        self.size_in_bytes += pooled.size_in_bytes;
+        self.transactions.insert(transaction_id, pooled);
+
+        Ok(evicted)
+    }
+
+    ///
+    /// Removes the given transaction ID from the memory pool, returning the transaction if it existed.
+    ///
+    pub fn remove_transaction(&mut self, transaction_id: &N::TransactionID) -> Option<Transaction<N>> {
+        let pooled = self.transactions.remove(transaction_id)?;
+        for serial_number in pooled.transaction.serial_numbers() {
+            self.serial_numbers.remove(serial_number);
+        }
+        for commitment in pooled.transaction.commitments() {
+            self.commitments.remove(commitment);
+        }
+        self.size_in_bytes -= pooled.size_in_bytes;
+        Some(pooled.transaction)
+    }
+
+    ///
+    /// Removes the given confirmed transactions from the memory pool, along with any unconfirmed
+    /// transactions that spend the same serial numbers or create the same commitments, as they can no longer be confirmed.
+    ///
+    pub fn remove_transactions(&mut self, transactions: &[Transaction<N>]) {
+        for transaction in transactions {
+            self.remove_transaction(&transaction.transaction_id());
+            for serial_number in transaction.serial_numbers() {
+                if let Some(transaction_id) = self.serial_numbers.get(serial_number).copied() {
+                    self.remove_transaction(&transaction_id);
+                }
+            }
+            for commitment in transaction.commitments() {
+                if let Some(transaction_id) = self.commitments.get(commitment).copied() {
+                    self.remove_transaction(&transaction_id);
+                }
+            }
+        }
+    }
+
+    ///
+    /// Removes the unconfirmed transactions that entered the memory pool more than
+    /// the expiry number of blocks before the given latest block height, returning their transaction IDs.
+    ///
+    pub fn remove_expired(&mut self, block_height: u32) -> Vec<N::TransactionID> {
+        let expired: Vec<N::TransactionID> = self
+            .transactions
+            .iter()
+            .filter(|(_, pooled)| block_height.saturating_sub(pooled.block_height) > self.expiry_in_blocks)
+            .map(|(transaction_id, _)| *transaction_id)
+            .collect();
+        for transaction_id in &expired {
+            self.remove_transaction(transaction_id);
+        }
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::dpc::testnet2::Testnet2;
+
+    use rand::{thread_rng, Rng, SeedableRng};
+    use rand_chacha::ChaChaRng;
+
+    /// Returns a new coinbase transaction, with random serial numbers and commitments.
+    fn new_transaction() -> Transaction<Testnet2> {
+        let rng = &mut thread_rng();
+        let address = Account::<Testnet2>::new(rng).address();
+        Transaction::<Testnet2>::new_coinbase(address, AleoAmount(rng.gen_range(1..1000)), rng).expect("Failed to create a transaction")
+    }
+
+    /// Returns two distinct coinbase transactions that spend the same serial numbers, as their noop inputs
+    /// are sampled from the same seed, while their outputs differ in amount.
+    fn new_conflicting_transactions() -> (Transaction<Testnet2>, Transaction<Testnet2>) {
+        let seed = thread_rng().gen();
+        let address = Account::<Testnet2>::new(&mut thread_rng()).address();
+        let new_coinbase = |amount| {
+            Transaction::<Testnet2>::new_coinbase(address, AleoAmount(amount), &mut ChaChaRng::seed_from_u64(seed))
+                .expect("Failed to create a transaction")
+        };
+        let (transaction, conflicting_transaction) = (new_coinbase(100), new_coinbase(200));
+
+        assert_ne!(transaction.transaction_id(), conflicting_transaction.transaction_id());
+        assert!(transaction.serial_numbers().eq(conflicting_transaction.serial_numbers()));
+        (transaction, conflicting_transaction)
+    }
+
+    /// Returns a new pooled transaction that pays the given fee, as coinbase transactions do not pay a fee.
+    fn new_pooled_transaction(fee: u64) -> PooledTransaction<Testnet2> {
+        let transaction = new_transaction();
+        PooledTransaction {
+            size_in_bytes: transaction.to_bytes_le().unwrap().len(),
+            transaction,
+            fee,
+            block_height: 1,
+        }
+    }
+
+    #[test]
+    fn test_add_and_remove_transaction() {
+        let mut memory_pool = MemoryPool::<Testnet2>::new(10, usize::MAX, 10);
+        let transaction = new_transaction();
+        let transaction_id = transaction.transaction_id();
+
+        assert_eq!(memory_pool.add_transaction(&transaction, 1).unwrap(), vec![]);
+        assert_eq!(memory_pool.len(), 1);
+        assert_eq!(memory_pool.size_in_bytes(), transaction.to_bytes_le().unwrap().len());
+        assert_eq!(memory_pool.get_transaction(&transaction_id), Some(&transaction));
+        assert!(
+            transaction
+                .serial_numbers()
+                .all(|serial_number| memory_pool.contains_serial_number(serial_number))
+        );
+        assert!(
+            transaction
+                .commitments()
+                .all(|commitment| memory_pool.contains_commitment(commitment))
+        );
+
+        // Ensure the same transaction is rejected.
+        assert_eq!(
+            memory_pool.add_transaction(&transaction, 1),
+            Err(TransactionError::AlreadyInMemoryPool(transaction_id.to_string()))
+        );
+
+        // Ensure a confirmed transaction is removed, along with its serial numbers and commitments.
+        memory_pool.remove_transactions(&[transaction.clone()]);
+        assert!(memory_pool.is_empty());
+        assert_eq!(memory_pool.size_in_bytes(), 0);
+        assert!(
+            !transaction
+                .serial_numbers()
+                .any(|serial_number| memory_pool.contains_serial_number(serial_number))
+        );
+        assert!(
+            !transaction
+                .commitments()
+                .any(|commitment| memory_pool.contains_commitment(commitment))
+        );
+    }
+
+    #[test]
+    fn test_memory_pool_limits() {
+        let mut memory_pool = MemoryPool::<Testnet2>::new(2, usize::MAX, 10);
+        memory_pool.add_transaction(&new_transaction(), 1).unwrap();
+        memory_pool.add_transaction(&new_transaction(), 1).unwrap();
+
+        // Ensure a transaction without a higher fee-per-byte is rejected once the memory pool is full.
+        match memory_pool.add_transaction(&new_transaction(), 1) {
+            Err(TransactionError::Rejected(_)) => (),
+            result => panic!("Expected the transaction to be rejected, found {:?}", result),
+        }
+        assert_eq!(memory_pool.len(), 2);
+
+        // Ensure a transaction larger than the memory pool is rejected.
+        let mut memory_pool = MemoryPool::<Testnet2>::new(2, 1, 10);
+        assert!(memory_pool.add_transaction(&new_transaction(), 1).is_err());
+        assert!(memory_pool.is_empty());
+    }
+
+    #[test]
+    fn test_evict_lower_fee_per_byte() {
+        let mut memory_pool = MemoryPool::<Testnet2>::new(2, usize::MAX, 10);
+        let low = new_pooled_transaction(10);
+        let medium = new_pooled_transaction(100);
+        let high = new_pooled_transaction(1000);
+        let (low_id, medium_id, high_id) = (
+            low.transaction.transaction_id(),
+            medium.transaction.transaction_id(),
+            high.transaction.transaction_id(),
+        );
+        memory_pool.add_pooled_transaction(low.clone()).unwrap();
+        memory_pool.add_pooled_transaction(medium.clone()).unwrap();
+
+        // Ensure a transaction with a higher fee-per-byte evicts the lowest one, which is returned.
+        assert_eq!(memory_pool.add_pooled_transaction(high.clone()).unwrap(), vec![low_id]);
+        assert_eq!(memory_pool.len(), 2);
+        assert!(!memory_pool.contains_transaction(&low_id));
+        assert!(
+            !low.transaction
+                .serial_numbers()
+                .any(|serial_number| memory_pool.contains_serial_number(serial_number))
+        );
+        assert_eq!(memory_pool.transactions(), vec![high.transaction, medium.transaction]);
+
+        // Ensure a transaction with a lower fee-per-byte than every unconfirmed transaction is rejected.
+        match memory_pool.add_pooled_transaction(new_pooled_transaction(1)) {
+            Err(TransactionError::Rejected(_)) => (),
+            result => panic!("Expected the transaction to be rejected, found {:?}", result),
+        }
+        assert!(memory_pool.contains_transaction(&medium_id));
+        assert!(memory_pool.contains_transaction(&high_id));
+    }
+
+    #[test]
+    fn test_conflicting_transactions() {
+        let mut memory_pool = MemoryPool::<Testnet2>::new(10, usize::MAX, 10);
+        let (transaction, conflicting_transaction) = new_conflicting_transactions();
+        memory_pool.add_transaction(&transaction, 1).unwrap();
+
+        // Ensure a transaction spending a serial number of an unconfirmed transaction is rejected.
+        let serial_number = conflicting_transaction.serial_numbers().next().unwrap();
+        assert_eq!(
+            memory_pool.add_transaction(&conflicting_transaction, 1),
+            Err(TransactionError::ConflictingSerialNumber(serial_number.to_string()))
+        );
+        assert_eq!(memory_pool.len(), 1);
+
+        // Ensure the unconfirmed transaction is evicted once a conflicting transaction is confirmed.
+        memory_pool.remove_transactions(&[conflicting_transaction.clone()]);
+        assert!(memory_pool.is_empty());
+        assert!(!memory_pool.contains_serial_number(serial_number));
+
+        // Ensure the conflicting transaction is accepted once it no longer conflicts.
+        assert_eq!(memory_pool.add_transaction(&conflicting_transaction, 1).unwrap(), vec![]);
+        assert_eq!(memory_pool.len(), 1);
+    }
+
+    #[test]
+    fn test_remove_expired() {
+        let mut memory_pool = MemoryPool::<Testnet2>::new(10, usize::MAX, 10);
+        let transaction = new_transaction();
+        memory_pool.add_transaction(&transaction, 5).unwrap();
+        memory_pool.add_transaction(&new_transaction(), 10).unwrap();
+
+        assert!(memory_pool.remove_expired(15).is_empty());
+        assert_eq!(memory_pool.remove_expired(16), vec![transaction.transaction_id()]);
+        assert_eq!(memory_pool.len(), 1);
+    }
+}
@@ -29,6 +29,10 @@ pub enum Message<N: Network, E: Environment> {
     BlockRequest(u32, u32),
     /// BlockResponse := (block)
     BlockResponse(Block<N>),
+    /// BlockHeaderRequest := (start_block_height, end_block_height (inclusive))
+    BlockHeaderRequest(u32, u32),
+    /// BlockHeaderResponse := (\[(block_hash, block_header)\])
+    BlockHeaderResponse(Vec<(N::BlockHash, BlockHeader<N>)>),
     /// ChallengeRequest := (version, listener_port, nonce, block_height)
     ChallengeRequest(u32, u16, u64, u32),
     /// ChallengeResponse := (block_header)
@@ -41,8 +45,8 @@ pub enum Message<N: Network, E: Environment> {
     PeerResponse(Vec<SocketAddr>),
     /// Ping := (version, block_height, block_hash)
     Ping(u32, u32, N::BlockHash),
-    /// Pong := (is_fork, block_locators)
-    Pong(Option<bool>, BlockLocators<N>),
+    /// Pong := (is_fork, cumulative_work, block_locators)
+    Pong(Option<bool>, u128, BlockLocators<N>),
     /// UnconfirmedBlock := (block)
     UnconfirmedBlock(Block<N>),
     /// UnconfirmedTransaction := (transaction)
@@ -67,6 +71,8 @@ impl<N: Network, E: Environment> Message<N, E> {
             Self::Pong(..) => "Pong",
             Self::UnconfirmedBlock(..) => "UnconfirmedBlock",
             Self::UnconfirmedTransaction(..) => "UnconfirmedTransaction",
+            Self::BlockHeaderRequest(..) => "BlockHeaderRequest",
+            Self::BlockHeaderResponse(..) => "BlockHeaderResponse",
             Self::Unused(..) => "Unused",
         }
     }
@@ -86,7 +92,9 @@ impl<N: Network, E: Environment> Message<N, E> {
             Self::Pong(..) => 8,
             Self::UnconfirmedBlock(..) => 9,
             Self::UnconfirmedTransaction(..) => 10,
-            Self::Unused(..) => 11,
+            Self::BlockHeaderRequest(..) => 11,
+            Self::BlockHeaderResponse(..) => 12,
+            Self::Unused(..) => 13,
         }
     }
 
@@ -104,7 +112,7 @@ impl<N: Network, E: Environment> Message<N, E> {
             Self::PeerRequest => Ok(vec![]),
             Self::PeerResponse(peer_ips) => Ok(bincode::serialize(peer_ips)?),
             Self::Ping(version, block_height, block_hash) => Ok(to_bytes_le![version, block_height, block_hash]?),
-            Self::Pong(is_fork, block_locators) => {
+            Self::Pong(is_fork, cumulative_work, block_locators) => {
                 let serialized_is_fork: u8 = match is_fork {
                     None => 0,
                     Some(fork) => match fork {
@@ -113,10 +121,17 @@ impl<N: Network, E: Environment> Message<N, E> {
                     },
                 };
 
-                Ok([vec![serialized_is_fork], bincode::serialize(block_locators)?].concat())
+                Ok([
+                    vec![serialized_is_fork],
+                    cumulative_work.to_le_bytes().to_vec(),
+                    bincode::serialize(block_locators)?,
+                ]
+                .concat())
             }
             Self::UnconfirmedBlock(block) => Ok(bincode::serialize(block)?),
             Self::UnconfirmedTransaction(transaction) => Ok(bincode::serialize(transaction)?),
+            Self::BlockHeaderRequest(start_block_height, end_block_height) => Ok(to_bytes_le![start_block_height, end_block_height]?),
+            Self::BlockHeaderResponse(block_headers) => Ok(bincode::serialize(block_headers)?),
             Self::Unused(_) => Ok(vec![]),
         }
     }
@@ -164,6 +179,10 @@ impl<N: Network, E: Environment> Message<N, E> {
                 bincode::deserialize(&data[8..])?,
             ),
             8 => {
+                // Ensure the message contains the fork indicator and the cumulative work.
+                if data.len() < 17 {
+                    return Err(anyhow!("Invalid 'Pong' message: {:?} {:?}", buffer, data));
+                }
                 let is_fork = match data[0] {
                     0 => None,
                     1 => Some(true),
@@ -171,10 +190,15 @@ impl<N: Network, E: Environment> Message<N, E> {
                     _ => return Err(anyhow!("Invalid 'Pong' message: {:?} {:?}", buffer, data)),
                 };
 
-                Self::Pong(is_fork, bincode::deserialize(&data[1..])?)
+                Self::Pong(is_fork, bincode::deserialize(&data[1..17])?, bincode::deserialize(&data[17..])?)
             }
             9 => Self::UnconfirmedBlock(bincode::deserialize(data)?),
             10 => Self::UnconfirmedTransaction(bincode::deserialize(data)?),
+            11 => match data.len() == 8 {
+                true => Self::BlockHeaderRequest(bincode::deserialize(&data[0..4])?, bincode::deserialize(&data[4..8])?),
+                false => return Err(anyhow!("Invalid 'BlockHeaderRequest' message: {:?} {:?}", buffer, data)),
+            },
+            12 => Self::BlockHeaderResponse(bincode::deserialize(data)?),
             _ => return Err(anyhow!("Invalid message ID {}", id)),
         };
 
@@ -0,0 +1,296 @@
+// Copyright (C) 2019-2021 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_ledger::storage::{DataMap, Map, Storage};
+use snarkvm::dpc::prelude::*;
+
+use anyhow::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::{path::Path, time::Duration};
+
+///
+/// An enum of the states of a block mined by this node.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MinedBlockStatus {
+    /// The block has been mined, but is not yet in the canonical chain.
+    Pending,
+    /// The block is in the canonical chain.
+    Canonical,
+    /// The block was not accepted into, or was reverted from, the canonical chain.
+    Orphaned,
+}
+
+///
+/// The persisted record of a block mined by this node.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinedBlock<N: Network> {
+    /// The height of the mined block.
+    pub block_height: u32,
+    /// The hash of the mined block.
+    pub block_hash: N::BlockHash,
+    /// The coinbase reward of the mined block.
+    pub block_reward: i64,
+    /// The timestamp at which the block was mined.
+    pub timestamp: i64,
+    /// The duration in milliseconds taken to mine the block.
+    pub time_taken_in_ms: u64,
+    /// The state of the mined block.
+    pub status: MinedBlockStatus,
+}
+
+///
+/// A summary of the blocks mined by this node.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct MiningStats {
+    /// The number of blocks mined.
+    pub blocks_mined: u32,
+    /// The number of mined blocks that are not yet in the canonical chain.
+    pub blocks_pending: u32,
+    /// The number of mined blocks in the canonical chain.
+    pub blocks_canonical: u32,
+    /// The number of mined blocks that were orphaned.
+    pub blocks_orphaned: u32,
+    /// The sum of the coinbase rewards of the mined blocks in the canonical chain.
+    pub total_reward: i64,
+    /// The average duration in milliseconds taken to mine a block.
+    pub average_time_in_ms: u64,
+}
+
+///
+/// A persistent history of the blocks mined by this node, which survives restarts.
+///
+#[derive(Clone, Debug)]
+pub struct MiningHistory<N: Network> {
+    /// The map of block hashes to their mined block records.
+    blocks: DataMap<N::BlockHash, MinedBlock<N>>,
+    /// The map of block heights to the hashes of the pending mined blocks at that height.
+    pending_blocks: DataMap<u32, Vec<N::BlockHash>>,
+}
+
+impl<N: Network> MiningHistory<N> {
+    ///
+    /// Opens the mining history from storage at the given path.
+    ///
+    pub fn open<S: Storage, P: AsRef<Path>>(path: P) -> Result<Self> {
+        let storage = S::open(path, 0, false)?;
+        Ok(Self {
+            blocks: storage.open_map("mined_blocks")?,
+            pending_blocks: storage.open_map("pending_blocks")?,
+        })
+    }
+
+    ///
+    /// Returns the list of mined blocks, from the highest to the lowest block height.
+    ///
+    pub fn mined_blocks(&self) -> Vec<MinedBlock<N>> {
+        let mut blocks: Vec<MinedBlock<N>> = self.blocks.values().collect();
+        blocks.sort_by(|a, b| b.block_height.cmp(&a.block_height));
+        blocks
+    }
+
+    ///
+    /// Returns a summary of the mined blocks.
+    ///
+    pub fn stats(&self) -> MiningStats {
+        let mut stats = MiningStats::default();
+        let mut total_time_in_ms = 0u64;
+        for block in self.blocks.values() {
+            stats.blocks_mined += 1;
+            total_time_in_ms = total_time_in_ms.saturating_add(block.time_taken_in_ms);
+            match block.status {
+                MinedBlockStatus::Pending => stats.blocks_pending += 1,
+                MinedBlockStatus::Canonical => {
+                    stats.blocks_canonical += 1;
+                    stats.total_reward = stats.total_reward.saturating_add(block.block_reward);
+                }
+                MinedBlockStatus::Orphaned => stats.blocks_orphaned += 1,
+            }
+        }
+        if stats.blocks_mined > 0 {
+            stats.average_time_in_ms = total_time_in_ms / stats.blocks_mined as u64;
+        }
+        stats
+    }
+
+    ///
+    /// Records the given block as mined by this node, in the given duration.
+    ///
+    pub fn add_mined_block(&self, block: &Block<N>, time_taken: Duration) -> Result<()> {
+        let mined_block = MinedBlock {
+            block_height: block.height(),
+            block_hash: block.hash(),
+            block_reward: Block::<N>::block_reward(block.height()).0,
+            timestamp: Utc::now().timestamp(),
+            time_taken_in_ms: time_taken.as_millis() as u64,
+            status: MinedBlockStatus::Pending,
+        };
+        self.add_pending_block(&mined_block)
+    }
+
+    ///
+    /// Updates the mined blocks upon the canonical chain advancing to the given block.
+    ///
+    /// If the given block was mined by this node, it is marked as canonical.
+    /// Any other pending block at or below the given block height can no longer be canonical, and is marked as orphaned.
+    ///
+    pub fn set_canonical(&self, block_height: u32, block_hash: &N::BlockHash) -> Result<()> {
+        // Mark the given block as canonical, if it was mined by this node.
+        if let Some(mut mined_block) = self.blocks.get(block_hash)? {
+            mined_block.status = MinedBlockStatus::Canonical;
+            self.blocks.insert(block_hash, &mined_block)?;
+        }
+
+        // Mark the other pending blocks at or below the given block height as orphaned.
+        let block_heights: Vec<u32> = self.pending_blocks.keys().filter(|height| *height <= block_height).collect();
+        for height in block_heights {
+            for hash in self.pending_blocks.get(&height)?.unwrap_or_default() {
+                if &hash == block_hash {
+                    continue;
+                }
+                if let Some(mut mined_block) = self.blocks.get(&hash)? {
+                    mined_block.status = MinedBlockStatus::Orphaned;
+                    self.blocks.insert(&hash, &mined_block)?;
+                }
+            }
+            self.pending_blocks.remove(&height)?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Flushes the mining history to persistent storage.
+    ///
+    pub fn flush(&self) -> Result<()> {
+        self.blocks.flush()
+    }
+
+    ///
+    /// Marks the given block as orphaned, if it was mined by this node.
+    ///
+    pub fn set_orphaned(&self, block_hash: &N::BlockHash) -> Result<()> {
+        match self.blocks.get(block_hash)? {
+            Some(mut mined_block) => {
+                self.remove_pending_block(mined_block.block_height, block_hash)?;
+                mined_block.status = MinedBlockStatus::Orphaned;
+                self.blocks.insert(block_hash, &mined_block)
+            }
+            None => Ok(()),
+        }
+    }
+
+    ///
+    /// Records the given pending block, and indexes it by its block height.
+    ///
+    fn add_pending_block(&self, mined_block: &MinedBlock<N>) -> Result<()> {
+        let mut hashes = self.pending_blocks.get(&mined_block.block_height)?.unwrap_or_default();
+        if !hashes.contains(&mined_block.block_hash) {
+            hashes.push(mined_block.block_hash);
+            self.pending_blocks.insert(&mined_block.block_height, &hashes)?;
+        }
+        self.blocks.insert(&mined_block.block_hash, mined_block)
+    }
+
+    ///
+    /// Removes the given block hash from the index of pending blocks at the given block height.
+    ///
+    fn remove_pending_block(&self, block_height: u32, block_hash: &N::BlockHash) -> Result<()> {
+        if let Some(mut hashes) = self.pending_blocks.get(&block_height)? {
+            hashes.retain(|hash| hash != block_hash);
+            match hashes.is_empty() {
+                true => self.pending_blocks.remove(&block_height)?,
+                false => self.pending_blocks.insert(&block_height, &hashes)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_ledger::storage::rocksdb::RocksDB;
+    use snarkvm::dpc::testnet2::Testnet2;
+
+    fn temp_dir() -> std::path::PathBuf {
+        tempfile::tempdir().expect("Failed to open temporary directory").into_path()
+    }
+
+    /// Inserts a pending mined block with the given height and hash into the mining history.
+    fn insert_pending(mining_history: &MiningHistory<Testnet2>, block_height: u32, block_hash: <Testnet2 as Network>::BlockHash) {
+        let mined_block = MinedBlock {
+            block_height,
+            block_hash,
+            block_reward: 100,
+            timestamp: 0,
+            time_taken_in_ms: 1000,
+            status: MinedBlockStatus::Pending,
+        };
+        mining_history.add_pending_block(&mined_block).unwrap();
+    }
+
+    #[test]
+    fn test_mining_history_persists() {
+        let directory = temp_dir();
+        let block = Testnet2::genesis_block();
+        {
+            let mining_history = MiningHistory::<Testnet2>::open::<RocksDB, _>(&directory).expect("Failed to open the mining history");
+            mining_history.add_mined_block(block, Duration::from_millis(2500)).unwrap();
+        }
+        {
+            let mining_history = MiningHistory::<Testnet2>::open::<RocksDB, _>(&directory).expect("Failed to open the mining history");
+            let mined_blocks = mining_history.mined_blocks();
+            assert_eq!(mined_blocks.len(), 1);
+            assert_eq!(mined_blocks[0].block_height, block.height());
+            assert_eq!(mined_blocks[0].block_hash, block.hash());
+            assert_eq!(mined_blocks[0].block_reward, Block::<Testnet2>::block_reward(block.height()).0);
+            assert_eq!(mined_blocks[0].time_taken_in_ms, 2500);
+            assert_eq!(mined_blocks[0].status, MinedBlockStatus::Pending);
+        }
+    }
+
+    #[test]
+    fn test_mining_history_canonical_and_orphaned() {
+        let mining_history = MiningHistory::<Testnet2>::open::<RocksDB, _>(temp_dir()).expect("Failed to open the mining history");
+        let block = Testnet2::genesis_block();
+        let (canonical_hash, competing_hash) = (block.hash(), block.previous_block_hash());
+
+        // Mine two competing blocks at the same height.
+        insert_pending(&mining_history, 1, canonical_hash);
+        insert_pending(&mining_history, 1, competing_hash);
+
+        // Advance the canonical chain to one of the blocks.
+        mining_history.set_canonical(1, &canonical_hash).unwrap();
+        let stats = mining_history.stats();
+        assert_eq!(stats.blocks_mined, 2);
+        assert_eq!(stats.blocks_pending, 0);
+        assert_eq!(stats.blocks_canonical, 1);
+        assert_eq!(stats.blocks_orphaned, 1);
+        assert_eq!(stats.total_reward, 100);
+        assert_eq!(stats.average_time_in_ms, 1000);
+        assert!(mining_history.pending_blocks.get(&1).unwrap().is_none());
+
+        // Revert the canonical block.
+        mining_history.set_orphaned(&canonical_hash).unwrap();
+        let stats = mining_history.stats();
+        assert_eq!(stats.blocks_canonical, 0);
+        assert_eq!(stats.blocks_orphaned, 2);
+        assert_eq!(stats.total_reward, 0);
+    }
+}
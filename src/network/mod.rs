@@ -15,13 +15,25 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 pub(crate) mod ledger;
-pub(crate) use ledger::{LedgerRequest, LedgerRouter};
+pub(crate) use ledger::{LedgerRequest, LedgerRouter, TransactionError};
+
+pub(crate) mod memory_pool;
+pub(crate) use memory_pool::MemoryPool;
 
 pub(crate) mod message;
 pub(crate) use message::*;
 
+pub(crate) mod mining_history;
+pub(crate) use mining_history::*;
+
+pub(crate) mod peer_book;
+pub(crate) use peer_book::*;
+
 pub(crate) mod peers;
 pub(crate) use peers::*;
 
 pub(crate) mod server;
 pub(crate) use server::Server;
+
+pub(crate) mod side_chain;
+pub(crate) use side_chain::SideChain;
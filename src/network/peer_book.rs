@@ -0,0 +1,372 @@
+// Copyright (C) 2019-2021 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::Environment;
+use snarkos_ledger::storage::{DataMap, Map, Storage};
+
+use anyhow::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::Path,
+};
+
+///
+/// An enum of misbehaviours that a peer may be penalized for.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Misbehaviour {
+    /// The peer sent a block that failed validation.
+    InvalidBlock,
+    /// The peer sent empty or malformed block locators.
+    InvalidBlockLocators,
+    /// The peer sent block headers that do not form a valid chain.
+    InvalidBlockHeaders,
+    /// The peer claimed more cumulative work than its block headers show.
+    InvalidCumulativeWork,
+    /// The peer requested blocks that could not be served.
+    InvalidBlockRequest,
+    /// The peer requested more blocks than permitted in one request.
+    OversizedBlockRequest,
+    /// The peer sent a block, or block headers, that were not requested.
+    UnrequestedBlockResponse,
+    /// The peer failed the handshake or violated the handshake protocol.
+    BadHandshake,
+    /// The peer flooded the node with messages.
+    Spam,
+}
+
+impl Misbehaviour {
+    ///
+    /// Returns the score penalty for the misbehaviour.
+    ///
+    pub fn score(&self) -> u32 {
+        match self {
+            Self::InvalidBlock => 50,
+            Self::InvalidBlockLocators => 50,
+            Self::InvalidBlockHeaders => 50,
+            Self::InvalidCumulativeWork => 50,
+            Self::InvalidBlockRequest => 10,
+            Self::OversizedBlockRequest => 20,
+            Self::UnrequestedBlockResponse => 10,
+            Self::BadHandshake => 20,
+            Self::Spam => 100,
+        }
+    }
+}
+
+///
+/// The persisted record of a peer known to the node.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    /// The timestamp of the last successful connection with the peer.
+    pub last_seen: Option<i64>,
+    /// The number of failed connection attempts with the peer since it was last seen.
+    pub failures: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MisbehaviourScore {
+    /// The misbehaviour score of the IP address, as of the timestamp.
+    pub score: u32,
+    /// The timestamp from which the misbehaviour score decays.
+    pub timestamp: i64,
+}
+
+impl MisbehaviourScore {
+    ///
+    /// Decays the misbehaviour score by one point for every elapsed `E::MISBEHAVIOUR_DECAY_IN_SECS`.
+    ///
+    fn decay<E: Environment>(&mut self, timestamp: i64) {
+        let decay_in_secs = E::MISBEHAVIOUR_DECAY_IN_SECS as i64;
+        let intervals = timestamp.saturating_sub(self.timestamp) / decay_in_secs;
+        if intervals >= self.score as i64 {
+            self.score = 0;
+            self.timestamp = timestamp;
+        } else if intervals > 0 {
+            self.score -= intervals as u32;
+            // Only advance the timestamp by the consumed intervals, to preserve partial progress.
+            self.timestamp += intervals * decay_in_secs;
+        }
+    }
+}
+
+///
+/// A persistent book of the peers known to the node, which survives restarts.
+///
+#[derive(Clone, Debug)]
+pub struct PeerBook {
+    /// The map of peer IPs to their peer information.
+    peers: DataMap<SocketAddr, PeerInfo>,
+    /// The map of IP addresses to their misbehaviour scores.
+    /// Scores apply to the IP address, so that a peer may not reset its score by reconnecting from another port.
+    scores: DataMap<IpAddr, MisbehaviourScore>,
+    /// The map of banned IP addresses to the timestamp until which each is banned.
+    /// Bans apply to the IP address, so that a banned peer may not evade its ban by connecting from another port.
+    bans: DataMap<IpAddr, i64>,
+}
+
+impl PeerBook {
+    ///
+    /// Opens the peer book from storage at the given path.
+    ///
+    pub fn open<S: Storage, P: AsRef<Path>>(path: P) -> Result<Self> {
+        let storage = S::open(path, 0, false)?;
+        Ok(Self {
+            peers: storage.open_map("peers")?,
+            scores: storage.open_map("scores")?,
+            bans: storage.open_map("bans")?,
+        })
+    }
+
+    ///
+    /// Returns the peer information for the given peer IP, if it exists.
+    ///
+    pub fn get(&self, peer_ip: &SocketAddr) -> Result<Option<PeerInfo>> {
+        self.peers.get(peer_ip)
+    }
+
+    ///
+    /// Returns the misbehaviour score of the given IP address, if it exists.
+    ///
+    pub fn get_score(&self, ip: IpAddr) -> Result<Option<MisbehaviourScore>> {
+        self.scores.get(&ip)
+    }
+
+    ///
+    /// Returns the list of peers in the peer book, with their peer information.
+    ///
+    pub fn peers(&self) -> Vec<(SocketAddr, PeerInfo)> {
+        self.peers.iter().collect()
+    }
+
+    ///
+    /// Returns the list of currently banned IP addresses, with the timestamp until which each is banned.
+    ///
+    pub fn banned_peers(&self) -> Vec<(IpAddr, i64)> {
+        let now = Utc::now().timestamp();
+        self.bans.iter().filter(|(_, banned_until)| now < *banned_until).collect()
+    }
+
+    ///
+    /// Returns `true` if the given IP address is currently banned.
+    ///
+    pub fn is_banned(&self, ip: IpAddr) -> bool {
+        match self.bans.get(&ip) {
+            Ok(Some(banned_until)) => Utc::now().timestamp() < banned_until,
+            _ => false,
+        }
+    }
+
+    ///
+    /// Adds the given peer IP to the peer book, if it does not already exist.
+    ///
+    pub fn add_peer(&self, peer_ip: SocketAddr) -> Result<()> {
+        match self.peers.contains_key(&peer_ip)? {
+            true => Ok(()),
+            false => self.peers.insert(&peer_ip, &PeerInfo::default()),
+        }
+    }
+
+    ///
+    /// Removes the given peer IP from the peer book.
+    ///
+    pub fn remove_peer(&self, peer_ip: &SocketAddr) -> Result<()> {
+        self.peers.remove(peer_ip)
+    }
+
+    ///
+    /// Records a successful connection with the given peer IP, resetting its failures.
+    ///
+    pub fn set_connected(&self, peer_ip: SocketAddr) -> Result<()> {
+        self.update(peer_ip, |info| {
+            info.last_seen = Some(Utc::now().timestamp());
+            info.failures = 0;
+        })
+    }
+
+    ///
+    /// Records a failed connection attempt with the given peer IP, returning the number of failures since it was last seen.
+    ///
+    pub fn add_failure(&self, peer_ip: SocketAddr) -> Result<u32> {
+        let mut failures = 0;
+        self.update(peer_ip, |info| {
+            info.failures = info.failures.saturating_add(1);
+            failures = info.failures;
+        })?;
+        Ok(failures)
+    }
+
+    ///
+    /// Penalizes the given peer IP for the given misbehaviour, returning `true` if the peer is now banned.
+    ///
+    /// The misbehaviour score is kept for the IP address of the peer, and decays over time. If the score reaches
+    /// `E::MISBEHAVIOUR_BAN_THRESHOLD`, the IP address is banned for `E::BAN_DURATION_IN_SECS`, and its score is reset.
+    ///
+    pub fn add_misbehaviour<E: Environment>(&self, peer_ip: SocketAddr, misbehaviour: Misbehaviour) -> Result<bool> {
+        let now = Utc::now().timestamp();
+        let mut score = match self.scores.get(&peer_ip.ip())? {
+            Some(score) => score,
+            None => MisbehaviourScore { score: 0, timestamp: now },
+        };
+        score.decay::<E>(now);
+        score.score = score.score.saturating_add(misbehaviour.score());
+
+        let is_banned = score.score >= E::MISBEHAVIOUR_BAN_THRESHOLD;
+        if is_banned {
+            score = MisbehaviourScore { score: 0, timestamp: now };
+        }
+        self.scores.insert(&peer_ip.ip(), &score)?;
+        if is_banned {
+            self.ban(peer_ip.ip(), now + E::BAN_DURATION_IN_SECS as i64)?;
+        }
+        Ok(is_banned)
+    }
+
+    ///
+    /// Bans the given IP address until the given timestamp.
+    ///
+    pub fn ban(&self, ip: IpAddr, banned_until: i64) -> Result<()> {
+        self.bans.insert(&ip, &banned_until)
+    }
+
+    ///
+    /// Lifts the ban on the IP address of the given peer IP, and resets the misbehaviour score of the IP address.
+    ///
+    pub fn unban(&self, peer_ip: SocketAddr) -> Result<()> {
+        self.bans.remove(&peer_ip.ip())?;
+        self.scores.remove(&peer_ip.ip())
+    }
+
+    ///
+    /// Flushes the peer book to persistent storage.
+    ///
+    pub fn flush(&self) -> Result<()> {
+        // The maps of the peer book share the same underlying storage, so flushing one map flushes all of them.
+        self.peers.flush()
+    }
+
+    ///
+    /// Applies the given update to the peer information of the given peer IP, adding the peer if it does not exist.
+    ///
+    fn update<F: FnOnce(&mut PeerInfo)>(&self, peer_ip: SocketAddr, update: F) -> Result<()> {
+        let mut info = self.peers.get(&peer_ip)?.unwrap_or_default();
+        update(&mut info);
+        self.peers.insert(&peer_ip, &info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+    use snarkos_ledger::storage::rocksdb::RocksDB;
+    use snarkvm::dpc::testnet2::Testnet2;
+
+    type E = Client<Testnet2>;
+
+    fn temp_dir() -> std::path::PathBuf {
+        tempfile::tempdir().expect("Failed to open temporary directory").into_path()
+    }
+
+    #[test]
+    fn test_peer_book_persists() {
+        let directory = temp_dir();
+        let peer_ip: SocketAddr = "127.0.0.1:4132".parse().unwrap();
+        {
+            let peer_book = PeerBook::open::<RocksDB, _>(&directory).expect("Failed to open the peer book");
+            peer_book.add_peer(peer_ip).unwrap();
+            assert_eq!(peer_book.add_failure(peer_ip).unwrap(), 1);
+            peer_book.ban(peer_ip.ip(), i64::MAX).unwrap();
+        }
+        {
+            let peer_book = PeerBook::open::<RocksDB, _>(&directory).expect("Failed to open the peer book");
+            let info = peer_book.get(&peer_ip).unwrap().expect("Missing peer in the peer book");
+            assert_eq!(info.failures, 1);
+            assert!(peer_book.is_banned(peer_ip.ip()));
+            assert_eq!(peer_book.peers(), vec![(peer_ip, info)]);
+        }
+    }
+
+    #[test]
+    fn test_peer_book_set_connected() {
+        let peer_book = PeerBook::open::<RocksDB, _>(temp_dir()).expect("Failed to open the peer book");
+        let peer_ip: SocketAddr = "127.0.0.1:4132".parse().unwrap();
+
+        assert_eq!(peer_book.add_failure(peer_ip).unwrap(), 1);
+        assert_eq!(peer_book.add_failure(peer_ip).unwrap(), 2);
+        peer_book.set_connected(peer_ip).unwrap();
+
+        let info = peer_book.get(&peer_ip).unwrap().unwrap();
+        assert_eq!(info.failures, 0);
+        assert!(info.last_seen.is_some());
+        assert!(!peer_book.is_banned(peer_ip.ip()));
+
+        peer_book.remove_peer(&peer_ip).unwrap();
+        assert!(peer_book.get(&peer_ip).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_peer_book_misbehaviour_ban() {
+        let peer_book = PeerBook::open::<RocksDB, _>(temp_dir()).expect("Failed to open the peer book");
+        let peer_ip: SocketAddr = "127.0.0.1:4132".parse().unwrap();
+
+        let other_peer_ip: SocketAddr = "127.0.0.1:54321".parse().unwrap();
+
+        // Accumulate misbehaviour until the peer is banned, even as it reconnects from another port.
+        let is_banned = peer_book
+            .add_misbehaviour::<E>(peer_ip, Misbehaviour::InvalidBlockLocators)
+            .unwrap();
+        assert!(!is_banned);
+        assert!(!peer_book.is_banned(peer_ip.ip()));
+        assert!(peer_book.add_misbehaviour::<E>(other_peer_ip, Misbehaviour::InvalidBlock).unwrap());
+        assert!(peer_book.is_banned(peer_ip.ip()));
+
+        // Check the ban applies to the IP address, regardless of the port.
+        assert!(peer_book.is_banned(other_peer_ip.ip()));
+
+        // Check the score is reset upon being banned.
+        assert_eq!(peer_book.get_score(peer_ip.ip()).unwrap().unwrap().score, 0);
+        assert_eq!(peer_book.banned_peers().len(), 1);
+
+        // Check the peer may be unbanned.
+        peer_book.unban(peer_ip).unwrap();
+        assert!(!peer_book.is_banned(peer_ip.ip()));
+        assert!(peer_book.get_score(peer_ip.ip()).unwrap().is_none());
+        assert!(peer_book.banned_peers().is_empty());
+    }
+
+    #[test]
+    fn test_misbehaviour_score_decay() {
+        let decay_in_secs = <E as Environment>::MISBEHAVIOUR_DECAY_IN_SECS as i64;
+        let mut score = MisbehaviourScore { score: 10, timestamp: 0 };
+
+        // Check a partial interval does not decay the score.
+        score.decay::<E>(decay_in_secs - 1);
+        assert_eq!(score.score, 10);
+
+        // Check the score decays by one point per interval, preserving partial progress.
+        score.decay::<E>(3 * decay_in_secs + 1);
+        assert_eq!(score.score, 7);
+        assert_eq!(score.timestamp, 3 * decay_in_secs);
+
+        // Check the score does not decay below zero.
+        score.decay::<E>(100 * decay_in_secs);
+        assert_eq!(score.score, 0);
+    }
+}
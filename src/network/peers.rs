@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{Environment, LedgerRequest, LedgerRouter, Message, NodeType};
+use crate::{Config, Environment, LedgerRequest, LedgerRouter, Message, Misbehaviour, NodeType, PeerBook};
 use snarkvm::dpc::prelude::*;
 
 use anyhow::{anyhow, Result};
@@ -22,7 +22,7 @@ use futures::SinkExt;
 use rand::{prelude::IteratorRandom, rngs::OsRng, thread_rng, Rng};
 use std::{
     collections::{HashMap, HashSet},
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     time::{Duration, Instant, SystemTime},
 };
 use tokio::{net::TcpStream, sync::mpsc, task, time::timeout};
@@ -59,8 +59,12 @@ pub enum PeersRequest<N: Network, E: Environment> {
     PeerConnected(SocketAddr, u64, OutboundRouter<N, E>),
     /// PeerDisconnected := (peer_ip)
     PeerDisconnected(SocketAddr),
-    /// PeerRestricted := (peer_ip)
-    PeerRestricted(SocketAddr),
+    /// PeerMisbehaved := (peer_ip, misbehaviour)
+    PeerMisbehaved(SocketAddr, Misbehaviour),
+    /// PeerBanned := (peer_ip, banned_until)
+    PeerBanned(SocketAddr, i64),
+    /// PeerUnbanned := (peer_ip)
+    PeerUnbanned(SocketAddr),
     /// SendPeerResponse := (peer_ip)
     SendPeerResponse(SocketAddr),
     /// ReceivePeerResponse := (\[peer_ip\])
@@ -75,12 +79,14 @@ pub struct Peers<N: Network, E: Environment> {
     local_ip: SocketAddr,
     /// The local nonce for this node session.
     local_nonce: u64,
+    /// The runtime configuration of this node.
+    config: Config,
+    /// The persistent book of peers known to this node.
+    peer_book: PeerBook,
     /// The map connected peer IPs to their nonce and outbound message router.
     connected_peers: HashMap<SocketAddr, (u64, OutboundRouter<N, E>)>,
     /// The set of candidate peer IPs.
     candidate_peers: HashSet<SocketAddr>,
-    /// The set of restricted peer IPs.
-    restricted_peers: HashMap<SocketAddr, Instant>,
     /// The map of peers to their first-seen port number, number of attempts, and timestamp of the last inbound connection request.
     seen_inbound_connections: HashMap<SocketAddr, ((u16, u32), SystemTime)>,
     /// The map of peers to a map of block hashes to their last seen timestamp.
@@ -93,25 +99,46 @@ pub struct Peers<N: Network, E: Environment> {
 
 impl<N: Network, E: Environment> Peers<N, E> {
     ///
-    /// Initializes a new instance of `Peers`.
+    /// Initializes a new instance of `Peers`, loading the candidate peers from the peer book.
     ///
-    pub(crate) fn new(local_ip: SocketAddr, local_nonce: Option<u64>) -> Self {
+    pub(crate) fn new(local_ip: SocketAddr, local_nonce: Option<u64>, config: Config, peer_book: PeerBook) -> Self {
         let local_nonce = match local_nonce {
             Some(nonce) => nonce,
             None => thread_rng().gen(),
         };
 
-        Self {
+        let mut peers = Self {
             local_ip,
             local_nonce,
+            config,
+            peer_book,
             connected_peers: Default::default(),
             candidate_peers: Default::default(),
-            restricted_peers: Default::default(),
             seen_outbound_blocks: Default::default(),
             seen_inbound_connections: Default::default(),
             seen_outbound_connections: Default::default(),
             seen_outbound_transactions: Default::default(),
-        }
+        };
+
+        // Load the known peers from the peer book, skipping any that are banned.
+        let candidate_peers: Vec<SocketAddr> = peers
+            .peer_book
+            .peers()
+            .into_iter()
+            .map(|(peer_ip, _)| peer_ip)
+            .filter(|peer_ip| !peers.peer_book.is_banned(peer_ip.ip()))
+            .collect();
+        peers.add_candidate_peers(&candidate_peers);
+
+        debug!("Loaded {} candidate peers from the peer book", peers.num_candidate_peers());
+        peers
+    }
+
+    ///
+    /// Returns the local address of this node.
+    ///
+    pub(crate) fn local_ip(&self) -> SocketAddr {
+        self.local_ip
     }
 
     ///
@@ -122,13 +149,17 @@ impl<N: Network, E: Environment> Peers<N, E> {
     }
 
     ///
-    /// Returns `true` if the given IP is restricted.
+    /// Returns `true` if the IP address of the given IP is banned, regardless of its port.
     ///
-    pub(crate) fn is_restricted(&self, ip: SocketAddr) -> bool {
-        match self.restricted_peers.get(&ip) {
-            Some(timestamp) => timestamp.elapsed().as_secs() < E::RADIO_SILENCE_IN_SECS,
-            None => false,
-        }
+    pub(crate) fn is_banned(&self, ip: SocketAddr) -> bool {
+        self.peer_book.is_banned(ip.ip())
+    }
+
+    ///
+    /// Returns the list of banned IP addresses, with the timestamp until which each is banned.
+    ///
+    pub(crate) fn banned_peers(&self) -> Vec<(IpAddr, i64)> {
+        self.peer_book.banned_peers()
     }
 
     ///
@@ -180,16 +211,16 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     debug!("Skipping connection request to {} (attempted to self-connect)", peer_ip);
                 }
                 // Ensure the node does not surpass the maximum number of peer connections.
-                else if self.num_connected_peers() >= E::MAXIMUM_NUMBER_OF_PEERS {
+                else if self.num_connected_peers() >= self.config.maximum_number_of_peers {
                     debug!("Skipping connection request to {} (maximum peers reached)", peer_ip);
                 }
                 // Ensure the peer is a new connection.
                 else if self.is_connected_to(peer_ip) {
                     debug!("Skipping connection request to {} (already connected)", peer_ip);
                 }
-                // Ensure the peer is not restricted.
-                else if self.is_restricted(peer_ip) {
-                    debug!("Skipping connection request to {} (restricted)", peer_ip);
+                // Ensure the peer is not banned.
+                else if self.is_banned(peer_ip) {
+                    debug!("Skipping connection request to {} (banned)", peer_ip);
                 }
                 // Attempt to open a TCP stream.
                 else {
@@ -219,11 +250,13 @@ impl<N: Network, E: Environment> Peers<N, E> {
                                 Err(error) => {
                                     trace!("Failed to connect to '{}': '{:?}'", peer_ip, error);
                                     self.candidate_peers.remove(&peer_ip);
+                                    self.add_connection_failure(peer_ip);
                                 }
                             },
                             Err(error) => {
                                 error!("Unable to reach '{}': '{:?}'", peer_ip, error);
                                 self.candidate_peers.remove(&peer_ip);
+                                self.add_connection_failure(peer_ip);
                             }
                         };
                     }
@@ -231,18 +264,15 @@ impl<N: Network, E: Environment> Peers<N, E> {
             }
             PeersRequest::Heartbeat(ledger_router) => {
                 // Ensure the number of connected peers is below the maximum threshold.
-                if self.num_connected_peers() > E::MAXIMUM_NUMBER_OF_PEERS {
+                if self.num_connected_peers() > self.config.maximum_number_of_peers {
                     debug!("Exceeded maximum number of connected peers");
 
                     // Determine the peers to disconnect from.
-                    let num_excess_peers = self.num_connected_peers() - E::MAXIMUM_NUMBER_OF_PEERS;
+                    let num_excess_peers = self.num_connected_peers() - self.config.maximum_number_of_peers;
                     let peer_ips_to_disconnect = self
                         .connected_peers
                         .iter()
-                        .filter(|(&peer_ip, _)| {
-                            let peer_str = peer_ip.to_string();
-                            !E::SYNC_NODES.contains(&peer_str.as_str()) && !E::PEER_NODES.contains(&peer_str.as_str())
-                        })
+                        .filter(|(peer_ip, _)| !self.config.sync_nodes.contains(peer_ip) && !self.config.peer_nodes.contains(peer_ip))
                         .take(num_excess_peers)
                         .map(|(&peer_ip, _)| peer_ip)
                         .collect::<Vec<SocketAddr>>();
@@ -255,19 +285,19 @@ impl<N: Network, E: Environment> Peers<N, E> {
                 }
 
                 // Skip if the number of connected peers is above the minimum threshold.
-                match self.num_connected_peers() < E::MINIMUM_NUMBER_OF_PEERS {
+                match self.num_connected_peers() < self.config.minimum_number_of_peers {
                     true => trace!("Sending request for more peer connections"),
                     false => return,
                 };
 
                 // If the current node is not a sync node, add the sync nodes to the list of candidate peers.
                 if E::NODE_TYPE != NodeType::Sync {
-                    let sync_nodes: Vec<SocketAddr> = E::SYNC_NODES.iter().map(|ip| ip.parse().unwrap()).collect();
+                    let sync_nodes = self.config.sync_nodes.clone();
                     self.add_candidate_peers(&sync_nodes);
                 }
 
                 // Add the peer nodes to the list of candidate peers.
-                let peer_nodes: Vec<SocketAddr> = E::PEER_NODES.iter().map(|ip| ip.parse().unwrap()).collect();
+                let peer_nodes = self.config.peer_nodes.clone();
                 self.add_candidate_peers(&peer_nodes);
 
                 // Attempt to connect to more peers if the number of connected peers is below the minimum threshold.
@@ -275,7 +305,7 @@ impl<N: Network, E: Environment> Peers<N, E> {
                 for peer_ip in self
                     .candidate_peers()
                     .iter()
-                    .choose_multiple(&mut OsRng::default(), E::MINIMUM_NUMBER_OF_PEERS)
+                    .choose_multiple(&mut OsRng::default(), self.config.minimum_number_of_peers)
                 {
                     if !self.is_connected_to(*peer_ip) {
                         trace!("Attempting connection to {}...", peer_ip);
@@ -302,16 +332,16 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     debug!("Skipping connection request to {} (attempted to self-connect)", peer_ip);
                 }
                 // Ensure the node does not surpass the maximum number of peer connections.
-                else if self.num_connected_peers() >= E::MAXIMUM_NUMBER_OF_PEERS {
+                else if self.num_connected_peers() >= self.config.maximum_number_of_peers {
                     debug!("Dropping connection request from {} (maximum peers reached)", peer_ip);
                 }
                 // Ensure the node is not already connected to this peer.
                 else if self.is_connected_to(peer_ip) {
                     debug!("Dropping connection request from {} (already connected)", peer_ip);
                 }
-                // Ensure the peer is not restricted.
-                else if self.is_restricted(peer_ip) {
-                    debug!("Dropping connection request from {} (restricted)", peer_ip);
+                // Ensure the peer is not banned.
+                else if self.is_banned(peer_ip) {
+                    debug!("Dropping connection request from {} (banned)", peer_ip);
                 }
                 // Spawn a handler to be run asynchronously.
                 else {
@@ -365,6 +395,10 @@ impl<N: Network, E: Environment> Peers<N, E> {
                 self.connected_peers.insert(peer_ip, (peer_nonce, outbound));
                 // Remove an entry for this `Peer` in the candidate peers, if it exists.
                 self.candidate_peers.remove(&peer_ip);
+                // Record the successful connection in the peer book.
+                if let Err(error) = self.peer_book.set_connected(peer_ip) {
+                    warn!("[PeerBook] {}", error);
+                }
             }
             PeersRequest::PeerDisconnected(peer_ip) => {
                 // Remove an entry for this `Peer` in the connected peers, if it exists.
@@ -376,9 +410,36 @@ impl<N: Network, E: Environment> Peers<N, E> {
                 self.seen_outbound_blocks.remove(&peer_ip);
                 self.seen_outbound_transactions.remove(&peer_ip);
             }
-            PeersRequest::PeerRestricted(peer_ip) => {
-                // Add an entry for this `Peer` in the restricted peers.
-                self.restricted_peers.insert(peer_ip, Instant::now());
+            PeersRequest::PeerMisbehaved(peer_ip, misbehaviour) => {
+                // Penalize the peer in the peer book, and disconnect from the peer if it is now banned.
+                match self.peer_book.add_misbehaviour::<E>(peer_ip, misbehaviour) {
+                    Ok(true) => {
+                        warn!("Banning {} for misbehaviour ({:?})", peer_ip.ip(), misbehaviour);
+                        self.disconnect_from_ip(peer_ip.ip()).await;
+                    }
+                    Ok(false) => debug!("Penalized {} for misbehaviour ({:?})", peer_ip, misbehaviour),
+                    Err(error) => warn!("[PeerBook] {}", error),
+                }
+            }
+            PeersRequest::PeerBanned(peer_ip, banned_until) => {
+                // Ban the peer in the peer book, and disconnect from the peer if it is connected.
+                match self.peer_book.ban(peer_ip.ip(), banned_until) {
+                    Ok(()) => {
+                        info!("Banned {} until {}", peer_ip.ip(), banned_until);
+                        self.disconnect_from_ip(peer_ip.ip()).await;
+                    }
+                    Err(error) => warn!("[PeerBook] {}", error),
+                }
+            }
+            PeersRequest::PeerUnbanned(peer_ip) => {
+                // Lift the ban on the peer in the peer book, and add it back as a candidate peer.
+                match self.peer_book.unban(peer_ip) {
+                    Ok(()) => {
+                        info!("Unbanned {}", peer_ip);
+                        self.add_candidate_peers(&[peer_ip]);
+                    }
+                    Err(error) => warn!("[PeerBook] {}", error),
+                }
             }
             PeersRequest::SendPeerResponse(recipient) => {
                 // Send a `PeerResponse` message.
@@ -390,6 +451,16 @@ impl<N: Network, E: Environment> Peers<N, E> {
         }
     }
 
+    ///
+    /// Sends a `Disconnect` message to every connected peer.
+    ///
+    pub(super) async fn disconnect_all(&mut self) {
+        for peer_ip in self.connected_peers() {
+            info!("Disconnecting from {}", peer_ip);
+            self.send(peer_ip, &Message::Disconnect).await;
+        }
+    }
+
     ///
     /// Adds the given peer IPs to the set of candidate peers.
     ///
@@ -406,8 +477,42 @@ impl<N: Network, E: Environment> Peers<N, E> {
                     || (peer_ip.ip().is_unspecified() || peer_ip.ip().is_loopback()) && peer_ip.port() == self.local_ip.port();
                 if !is_self && !self.is_connected_to(*peer_ip) && !self.candidate_peers.contains(peer_ip) {
                     self.candidate_peers.insert(*peer_ip);
+                    // Record the candidate peer in the peer book.
+                    if let Err(error) = self.peer_book.add_peer(*peer_ip) {
+                        warn!("[PeerBook] {}", error);
+                    }
+                }
+            }
+        }
+    }
+
+    ///
+    /// Removes the candidate peers with the given IP address, and disconnects from the connected peers with it.
+    ///
+    async fn disconnect_from_ip(&mut self, ip: IpAddr) {
+        self.candidate_peers.retain(|peer_ip| peer_ip.ip() != ip);
+        for peer_ip in self.connected_peers().into_iter().filter(|peer_ip| peer_ip.ip() == ip) {
+            self.send(peer_ip, &Message::Disconnect).await;
+        }
+    }
+
+    ///
+    /// Records a failed connection attempt with the given peer IP in the peer book.
+    ///
+    /// If the peer has surpassed the maximum number of connection failures, it is removed from the peer book,
+    /// unless it is banned, in which case it is retained until the ban expires.
+    ///
+    fn add_connection_failure(&mut self, peer_ip: SocketAddr) {
+        match self.peer_book.add_failure(peer_ip) {
+            Ok(failures) => {
+                if failures > E::MAXIMUM_CONNECTION_FAILURES && !self.is_banned(peer_ip) {
+                    trace!("Removing {} from the peer book (exceeded maximum connection failures)", peer_ip);
+                    if let Err(error) = self.peer_book.remove_peer(&peer_ip) {
+                        warn!("[PeerBook] {}", error);
+                    }
                 }
             }
+            Err(error) => warn!("[PeerBook] {}", error),
         }
     }
 
@@ -523,7 +628,7 @@ impl<N: Network, E: Environment> Peer<N, E> {
         let mut outbound_socket = Framed::new(stream, Message::<N, E>::PeerRequest);
 
         // Perform the handshake before proceeding.
-        let (peer_ip, peer_nonce) = Peer::handshake(&mut outbound_socket, local_ip, local_nonce, connected_nonces).await?;
+        let (peer_ip, peer_nonce) = Peer::handshake(&mut outbound_socket, local_ip, local_nonce, peers_router, connected_nonces).await?;
 
         // Send the first ping sequence to the peer.
         ledger_router.send(LedgerRequest::SendPing(peer_ip)).await?;
@@ -564,6 +669,7 @@ impl<N: Network, E: Environment> Peer<N, E> {
         outbound_socket: &mut Framed<TcpStream, Message<N, E>>,
         local_ip: SocketAddr,
         local_nonce: u64,
+        peers_router: &PeersRouter<N, E>,
         connected_nonces: &[u64],
     ) -> Result<(SocketAddr, u64)> {
         // Get the IP address of the peer.
@@ -644,7 +750,13 @@ impl<N: Network, E: Environment> Peer<N, E> {
                         match block_header.height() == CHALLENGE_HEIGHT && &block_header == genesis_block_header && block_header.is_valid()
                         {
                             true => Ok((peer_ip, peer_nonce)),
-                            false => return Err(anyhow!("Challenge response from {} failed, received '{}'", peer_ip, block_header)),
+                            false => {
+                                // Penalize the peer for failing the challenge.
+                                peers_router
+                                    .send(PeersRequest::PeerMisbehaved(peer_ip, Misbehaviour::BadHandshake))
+                                    .await?;
+                                Err(anyhow!("Challenge response from {} failed, received '{}'", peer_ip, block_header))
+                            }
                         }
                     }
                     message => {
@@ -734,9 +846,24 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                         warn!("[BlockResponse] {}", error);
                                     }
                                 }
+                                Message::BlockHeaderRequest(start_block_height, end_block_height) => {
+                                    // Route the `BlockHeaderRequest` to the ledger.
+                                    if let Err(error) = ledger_router.send(LedgerRequest::BlockHeaderRequest(peer_ip, start_block_height, end_block_height)).await {
+                                        warn!("[BlockHeaderRequest] {}", error);
+                                    }
+                                }
+                                Message::BlockHeaderResponse(block_headers) => {
+                                    // Route the `BlockHeaderResponse` to the ledger.
+                                    if let Err(error) = ledger_router.send(LedgerRequest::BlockHeaderResponse(peer_ip, block_headers)).await {
+                                        warn!("[BlockHeaderResponse] {}", error);
+                                    }
+                                }
                                 Message::ChallengeRequest(..) | Message::ChallengeResponse(..) => {
                                     // Peer is not following the protocol.
                                     warn!("Peer {} is not following the protocol", peer_ip);
+                                    if let Err(error) = peers_router.send(PeersRequest::PeerMisbehaved(peer_ip, Misbehaviour::BadHandshake)).await {
+                                        warn!("[PeerMisbehaved] {}", error);
+                                    }
                                     break;
                                 },
                                 Message::Disconnect => {
@@ -771,9 +898,10 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                         warn!("[Ping] {}", error);
                                     }
                                 },
-                                Message::Pong(is_fork, block_locators) => {
+                                Message::Pong(is_fork, cumulative_work, block_locators) => {
                                     // Route the `Pong` to the ledger.
-                                    if let Err(error) = ledger_router.send(LedgerRequest::Pong(peer_ip, is_fork, block_locators)).await {
+                                    let request = LedgerRequest::Pong(peer_ip, is_fork, cumulative_work, block_locators);
+                                    if let Err(error) = ledger_router.send(request).await {
                                         warn!("[Pong] {}", error);
                                     }
                                 }
@@ -782,9 +910,9 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                     let frequency = peer.seen_inbound_blocks.values().filter(|t| t.elapsed().unwrap().as_secs() <= 5).count();
                                     if frequency >= 5 {
                                         warn!("Dropping {} for spamming unconfirmed blocks (frequency = {})", peer_ip, frequency);
-                                        // Send a `PeerRestricted` message.
-                                        if let Err(error) = peers_router.send(PeersRequest::PeerRestricted(peer_ip)).await {
-                                            warn!("[PeerRestricted] {}", error);
+                                        // Send a `PeerMisbehaved` message.
+                                        if let Err(error) = peers_router.send(PeersRequest::PeerMisbehaved(peer_ip, Misbehaviour::Spam)).await {
+                                            warn!("[PeerMisbehaved] {}", error);
                                         }
                                         break;
                                     }
@@ -808,9 +936,9 @@ impl<N: Network, E: Environment> Peer<N, E> {
                                     let frequency = peer.seen_inbound_transactions.values().filter(|t| t.elapsed().unwrap().as_secs() <= 5).count();
                                     if frequency >= 500 {
                                         warn!("Dropping {} for spamming unconfirmed transactions (frequency = {})", peer_ip, frequency);
-                                        // Send a `PeerRestricted` message.
-                                        if let Err(error) = peers_router.send(PeersRequest::PeerRestricted(peer_ip)).await {
-                                            warn!("[PeerRestricted] {}", error);
+                                        // Send a `PeerMisbehaved` message.
+                                        if let Err(error) = peers_router.send(PeersRequest::PeerMisbehaved(peer_ip, Misbehaviour::Spam)).await {
+                                            warn!("[PeerMisbehaved] {}", error);
                                         }
                                         break;
                                     }
@@ -17,19 +17,29 @@
 use crate::{
     helpers::Tasks,
     ledger::{Ledger, LedgerRequest, LedgerRouter},
+    mining_history::MiningHistory,
+    peer_book::PeerBook,
     peers::{Peers, PeersRequest, PeersRouter},
-    rpc::initialize_rpc_server,
+    rpc::{initialize_rpc_server, RpcCredentials},
+    Config,
     Environment,
     NodeType,
 };
 use snarkos_ledger::{storage::rocksdb::RocksDB, LedgerState};
 use snarkvm::prelude::*;
 
-use anyhow::Result;
-use std::{net::SocketAddr, sync::Arc, time::Duration};
+use anyhow::{anyhow, Result};
+use fs2::FileExt;
+use std::{
+    fs::{self, File},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 use tokio::{
     net::TcpListener,
-    sync::{mpsc, RwLock},
+    sync::{mpsc, Notify, RwLock},
     task,
 };
 
@@ -45,8 +55,16 @@ pub(crate) struct Server<N: Network, E: Environment> {
     ledger: Arc<RwLock<Ledger<N, E>>>,
     /// The ledger router of the node.
     ledger_router: LedgerRouter<N, E>,
+    /// The persistent book of the peers known to the node.
+    peer_book: PeerBook,
+    /// The persistent history of the blocks mined by the node.
+    mining_history: MiningHistory<N>,
     /// The list of tasks spawned by the node.
     tasks: Tasks<task::JoinHandle<()>>,
+    /// The exclusive lock on the data directory, held for the lifetime of the node.
+    _data_dir_lock: File,
+    /// The path to the RPC cookie file, if the RPC credentials were generated on startup.
+    rpc_cookie_path: Option<PathBuf>,
 }
 
 impl<N: Network, E: Environment> Server<N, E> {
@@ -54,44 +72,65 @@ impl<N: Network, E: Environment> Server<N, E> {
     /// Starts the connection listener for peers.
     ///
     #[inline]
-    pub(crate) async fn initialize(
-        node_port: u16,
-        rpc_port: u16,
-        username: String,
-        password: String,
-        miner: Option<Address<N>>,
-    ) -> Result<Self> {
+    pub(crate) async fn initialize(config: Config, username: String, password: Option<String>, miner: Option<Address<N>>) -> Result<Self> {
         // Initialize a new TCP listener at the given IP.
-        let (local_ip, listener) = match TcpListener::bind(&format!("0.0.0.0:{}", node_port)).await {
+        let (listener_ip, listener) = match TcpListener::bind(SocketAddr::new(config.node_bind, config.node_port)).await {
             Ok(listener) => (listener.local_addr().expect("Failed to fetch the local IP"), listener),
             Err(error) => panic!("Failed to bind listener: {:?}. Check if another Aleo node is running", error),
         };
+        // Advertise the external address to peers, if one is specified, otherwise the listener address.
+        let local_ip = config.external_addr.unwrap_or(listener_ip);
 
+        // Acquire the lock on the data directory, to ensure no other node is using it.
+        let data_dir_lock = Self::lock_data_dir(&config.data_dir)?;
         // Initialize the ledger storage path.
-        let storage_path = format!(".ledger-{}", (node_port as u16 - 4130) as u8);
+        let storage_path = config.data_dir.join("ledger");
+        // Initialize the RPC credentials, generating a random password in the cookie file if none is given.
+        let (rpc_credentials, rpc_cookie_path) = match password {
+            Some(password) => (RpcCredentials { username, password }, None),
+            None => {
+                let rpc_cookie_path = config.data_dir.join(".cookie");
+                let rpc_credentials = RpcCredentials::from_cookie_file(&rpc_cookie_path, username)?;
+                info!("Generated the RPC credentials in {}", rpc_cookie_path.display());
+                (rpc_credentials, Some(rpc_cookie_path))
+            }
+        };
 
         // Initialize the tasks handler.
         let mut tasks = Tasks::new();
 
+        // Open the peer book from storage.
+        let peer_book = PeerBook::open::<RocksDB, _>(config.data_dir.join("peers"))?;
         // Initialize a new instance for managing peers.
-        let (peers, peers_router) = Self::initialize_peers(&mut tasks, local_ip);
+        let (peers, peers_router) = Self::initialize_peers(&mut tasks, local_ip, &config, peer_book.clone());
+        // Open the mining history from storage.
+        let mining_history = MiningHistory::<N>::open::<RocksDB, _>(config.data_dir.join("mining"))?;
         // Initialize a new instance for managing the ledger.
-        let (ledger, ledger_router) = Self::initialize_ledger(&mut tasks, &storage_path, &peers_router)?;
+        let (ledger, ledger_router) = Self::initialize_ledger(
+            &mut tasks,
+            &storage_path,
+            &config,
+            peer_book.clone(),
+            mining_history.clone(),
+            &peers_router,
+        )?;
 
         // Initialize the connection listener for new peers.
-        Self::initialize_listener(&mut tasks, local_ip, listener, &peers_router, &ledger_router);
+        Self::initialize_listener(&mut tasks, listener_ip, listener, &peers_router, &ledger_router);
         // Initialize a new instance of the heartbeat.
-        Self::initialize_heartbeat(&mut tasks, &peers_router, &ledger_router);
+        Self::initialize_heartbeat(&mut tasks, &config, &peers_router, &ledger_router);
         // Initialize a new instance of the miner.
-        Self::initialize_miner(&mut tasks, local_ip, miner, &ledger_router);
+        let miner_signal = ledger.read().await.miner_signal();
+        Self::initialize_miner(&mut tasks, local_ip, miner, miner_signal, &ledger_router);
 
         // Initialize a new instance of the RPC server.
         tasks.append(initialize_rpc_server::<N, E>(
-            format!("0.0.0.0:{}", rpc_port).parse()?,
-            username,
-            password,
+            SocketAddr::new(config.rpc_bind, config.rpc_port),
+            rpc_credentials,
             &peers,
+            &peers_router,
             LedgerState::open::<RocksDB, _>(&storage_path, true)?,
+            &ledger,
             &ledger_router,
         ));
 
@@ -100,7 +139,11 @@ impl<N: Network, E: Environment> Server<N, E> {
             peers_router,
             ledger,
             ledger_router,
+            peer_book,
+            mining_history,
             tasks,
+            _data_dir_lock: data_dir_lock,
+            rpc_cookie_path,
         })
     }
 
@@ -114,13 +157,58 @@ impl<N: Network, E: Environment> Server<N, E> {
         Ok(())
     }
 
+    ///
+    /// Returns the persistent history of the blocks mined by the node.
+    ///
+    #[inline]
+    pub(crate) fn mining_history(&self) -> &MiningHistory<N> {
+        &self.mining_history
+    }
+
     ///
     /// Disconnects from peers and proceeds to shut down the node.
     ///
     #[inline]
-    pub(crate) fn shut_down(&self) {
+    pub(crate) async fn shut_down(&self) {
         info!("Shutting down...");
+        // Acquire the ledger write lock, which waits for any in-flight ledger update (e.g. `add_block`) to complete.
+        // Then, stop the miner, halt further ledger updates, and flush the ledger to storage.
+        if let Err(error) = self.ledger.write().await.shut_down() {
+            error!("Failed to flush the ledger: {}", error);
+        }
+        // Send a `Disconnect` message to every connected peer.
+        self.peers.write().await.disconnect_all().await;
+        // Flush the peer book and the mining history to storage.
+        if let Err(error) = self.peer_book.flush() {
+            error!("Failed to flush the peer book: {}", error);
+        }
+        if let Err(error) = self.mining_history.flush() {
+            error!("Failed to flush the mining history: {}", error);
+        }
+        // Abort the spawned tasks of the node.
         self.tasks.flush();
+        // Remove the RPC cookie file, as its credentials are no longer valid.
+        if let Some(rpc_cookie_path) = &self.rpc_cookie_path {
+            if let Err(error) = fs::remove_file(rpc_cookie_path) {
+                warn!("Failed to remove the RPC cookie file: {}", error);
+            }
+        }
+    }
+
+    ///
+    /// Creates the data directory, if it does not exist, and acquires an exclusive lock on it.
+    ///
+    #[inline]
+    fn lock_data_dir(data_dir: &Path) -> Result<File> {
+        fs::create_dir_all(data_dir)?;
+        let lock_file = File::create(data_dir.join(".lock"))?;
+        match lock_file.try_lock_exclusive() {
+            Ok(()) => Ok(lock_file),
+            Err(_) => Err(anyhow!(
+                "Failed to lock the data directory {}. Check if another Aleo node is using it",
+                data_dir.display()
+            )),
+        }
     }
 
     ///
@@ -128,9 +216,14 @@ impl<N: Network, E: Environment> Server<N, E> {
     ///
     #[inline]
     #[allow(clippy::type_complexity)]
-    fn initialize_peers(tasks: &mut Tasks<task::JoinHandle<()>>, local_ip: SocketAddr) -> (Arc<RwLock<Peers<N, E>>>, PeersRouter<N, E>) {
+    fn initialize_peers(
+        tasks: &mut Tasks<task::JoinHandle<()>>,
+        local_ip: SocketAddr,
+        config: &Config,
+        peer_book: PeerBook,
+    ) -> (Arc<RwLock<Peers<N, E>>>, PeersRouter<N, E>) {
         // Initialize the `Peers` struct.
-        let peers = Arc::new(RwLock::new(Peers::new(local_ip, None)));
+        let peers = Arc::new(RwLock::new(Peers::new(local_ip, None, config.clone(), peer_book)));
 
         // Initialize an mpsc channel for sending requests to the `Peers` struct.
         let (peers_router, mut peers_handler) = mpsc::channel(1024);
@@ -157,11 +250,14 @@ impl<N: Network, E: Environment> Server<N, E> {
     #[allow(clippy::type_complexity)]
     fn initialize_ledger(
         tasks: &mut Tasks<task::JoinHandle<()>>,
-        storage_path: &str,
+        storage_path: &Path,
+        config: &Config,
+        peer_book: PeerBook,
+        mining_history: MiningHistory<N>,
         peers_router: &PeersRouter<N, E>,
     ) -> Result<(Arc<RwLock<Ledger<N, E>>>, LedgerRouter<N, E>)> {
         // Open the ledger from storage.
-        let ledger = Ledger::<N, E>::open::<RocksDB, _>(storage_path)?;
+        let ledger = Ledger::<N, E>::open::<RocksDB, _>(storage_path, config.clone(), peer_book, mining_history)?;
         let ledger = Arc::new(RwLock::new(ledger));
 
         // Initialize an mpsc channel for sending requests to the `Ledger` struct.
@@ -170,11 +266,16 @@ impl<N: Network, E: Environment> Server<N, E> {
         // Initialize the ledger router process.
         let peers_router = peers_router.clone();
         let ledger_clone = ledger.clone();
+        let ledger_router_clone = ledger_router.clone();
         tasks.append(task::spawn(async move {
             // Asynchronously wait for a ledger request.
             while let Some(request) = ledger_handler.recv().await {
                 // Hold the ledger write lock briefly, to update the state of the ledger.
-                ledger_clone.write().await.update(request, &peers_router).await;
+                ledger_clone
+                    .write()
+                    .await
+                    .update(request, &peers_router, &ledger_router_clone)
+                    .await;
             }
         }));
 
@@ -187,7 +288,7 @@ impl<N: Network, E: Environment> Server<N, E> {
     #[inline]
     fn initialize_listener(
         tasks: &mut Tasks<task::JoinHandle<()>>,
-        local_ip: SocketAddr,
+        listener_ip: SocketAddr,
         listener: TcpListener,
         peers_router: &PeersRouter<N, E>,
         ledger_router: &LedgerRouter<N, E>,
@@ -195,7 +296,7 @@ impl<N: Network, E: Environment> Server<N, E> {
         let peers_router = peers_router.clone();
         let ledger_router = ledger_router.clone();
         tasks.append(task::spawn(async move {
-            info!("Listening for peers at {}", local_ip);
+            info!("Listening for peers at {}", listener_ip);
             loop {
                 // Asynchronously wait for an inbound TcpStream.
                 match listener.accept().await {
@@ -218,7 +319,13 @@ impl<N: Network, E: Environment> Server<N, E> {
     /// Initialize a new instance of the heartbeat.
     ///
     #[inline]
-    fn initialize_heartbeat(tasks: &mut Tasks<task::JoinHandle<()>>, peers_router: &PeersRouter<N, E>, ledger_router: &LedgerRouter<N, E>) {
+    fn initialize_heartbeat(
+        tasks: &mut Tasks<task::JoinHandle<()>>,
+        config: &Config,
+        peers_router: &PeersRouter<N, E>,
+        ledger_router: &LedgerRouter<N, E>,
+    ) {
+        let heartbeat = Duration::from_secs(config.heartbeat_in_secs);
         let peers_router = peers_router.clone();
         let ledger_router = ledger_router.clone();
         tasks.append(task::spawn(async move {
@@ -233,8 +340,8 @@ impl<N: Network, E: Environment> Server<N, E> {
                 if let Err(error) = ledger_router.send(request).await {
                     error!("Failed to send heartbeat to ledger: {}", error)
                 }
-                // Sleep for `E::HEARTBEAT_IN_SECS` seconds.
-                tokio::time::sleep(Duration::from_secs(E::HEARTBEAT_IN_SECS)).await;
+                // Sleep for `heartbeat_in_secs` seconds.
+                tokio::time::sleep(heartbeat).await;
             }
         }));
     }
@@ -247,25 +354,31 @@ impl<N: Network, E: Environment> Server<N, E> {
         tasks: &mut Tasks<task::JoinHandle<()>>,
         local_ip: SocketAddr,
         miner: Option<Address<N>>,
+        miner_signal: Arc<Notify>,
         ledger_router: &LedgerRouter<N, E>,
     ) {
         if E::NODE_TYPE == NodeType::Miner {
-            if let Some(recipient) = miner {
-                let ledger_router = ledger_router.clone();
-                tasks.append(task::spawn(async move {
-                    loop {
-                        // Start the mining process.
-                        let request = LedgerRequest::Mine(local_ip, recipient, ledger_router.clone());
-                        if let Err(error) = ledger_router.send(request).await {
+            let ledger_router = ledger_router.clone();
+            tasks.append(task::spawn(async move {
+                match miner {
+                    // Set the recipient of the coinbase rewards.
+                    Some(recipient) => {
+                        if let Err(error) = ledger_router.send(LedgerRequest::SetMiningRecipient(recipient)).await {
                             error!("Failed to send request to ledger: {}", error);
                         }
-                        // Sleep for 2 seconds.
-                        tokio::time::sleep(Duration::from_secs(2)).await;
                     }
-                }));
-            } else {
-                error!("Missing miner address. Please specify an Aleo address in order to mine");
-            }
+                    None => warn!("Missing miner address. The miner starts once a recipient is set with `setminingrecipient`"),
+                }
+                loop {
+                    // Wait until the ledger is ready to mine the next block.
+                    miner_signal.notified().await;
+                    // Start the mining process.
+                    let request = LedgerRequest::Mine(local_ip, ledger_router.clone());
+                    if let Err(error) = ledger_router.send(request).await {
+                        error!("Failed to send request to ledger: {}", error);
+                    }
+                }
+            }));
         }
     }
 }
@@ -0,0 +1,209 @@
+// Copyright (C) 2019-2021 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkvm::dpc::prelude::*;
+
+use std::{collections::BTreeMap, net::SocketAddr};
+
+///
+/// A side chain of blocks from a peer, which branches off the canon chain after the common ancestor.
+///
+/// The expected block hashes are given by the validated block headers of the side chain,
+/// which may be extended with further block headers until the side chain proves a larger cumulative work.
+/// Its blocks are downloaded in full before the ledger switches to it, so that the canon chain
+/// is left unchanged if the peer fails to provide the side chain.
+///
+#[derive(Clone, Debug)]
+pub(crate) struct SideChain<N: Network> {
+    /// The peer that the side chain is downloaded from.
+    peer_ip: SocketAddr,
+    /// The block height of the common ancestor with the canon chain.
+    common_ancestor: u32,
+    /// The expected block hashes of the side chain, in increasing order of block height.
+    block_hashes: Vec<N::BlockHash>,
+    /// The latest validated block header of the side chain.
+    latest_block_header: BlockHeader<N>,
+    /// The cumulative work of the side chain up to its latest block header.
+    cumulative_work: u128,
+    /// The map of block heights to their downloaded blocks.
+    blocks: BTreeMap<u32, Block<N>>,
+}
+
+impl<N: Network> SideChain<N> {
+    /// Initializes a new instance of a side chain, from the given block header and cumulative work of the common ancestor.
+    pub(crate) fn new(peer_ip: SocketAddr, common_ancestor: u32, block_header: BlockHeader<N>, cumulative_work: u128) -> Self {
+        Self {
+            peer_ip,
+            common_ancestor,
+            block_hashes: Default::default(),
+            latest_block_header: block_header,
+            cumulative_work,
+            blocks: Default::default(),
+        }
+    }
+
+    /// Returns the peer that the side chain is downloaded from.
+    pub(crate) fn peer_ip(&self) -> SocketAddr {
+        self.peer_ip
+    }
+
+    /// Returns the block height of the common ancestor with the canon chain.
+    pub(crate) fn common_ancestor(&self) -> u32 {
+        self.common_ancestor
+    }
+
+    /// Returns the latest validated block header of the side chain.
+    pub(crate) fn latest_block_header(&self) -> &BlockHeader<N> {
+        &self.latest_block_header
+    }
+
+    /// Returns the block height of the latest validated block header of the side chain.
+    pub(crate) fn latest_block_height(&self) -> u32 {
+        self.common_ancestor + self.number_of_blocks()
+    }
+
+    /// Returns the number of blocks in the side chain after the common ancestor.
+    pub(crate) fn number_of_blocks(&self) -> u32 {
+        self.block_hashes.len() as u32
+    }
+
+    /// Returns the cumulative work of the side chain up to its latest block header.
+    pub(crate) fn cumulative_work(&self) -> u128 {
+        self.cumulative_work
+    }
+
+    /// Extends the side chain with the given validated block headers, and their resulting cumulative work.
+    pub(crate) fn extend(&mut self, block_headers: Vec<(N::BlockHash, BlockHeader<N>)>, cumulative_work: u128) {
+        for (block_hash, block_header) in block_headers {
+            self.block_hashes.push(block_hash);
+            self.latest_block_header = block_header;
+        }
+        self.cumulative_work = cumulative_work;
+    }
+
+    /// Returns the expected block hash for the given block height, if it is in the side chain.
+    pub(crate) fn get_block_hash(&self, block_height: u32) -> Option<N::BlockHash> {
+        let index = block_height.checked_sub(self.common_ancestor + 1)?;
+        self.block_hashes.get(index as usize).copied()
+    }
+
+    /// Returns `true` if the given block is an expected block of the side chain.
+    pub(crate) fn contains_block_hash(&self, block: &Block<N>) -> bool {
+        self.get_block_hash(block.height()) == Some(block.hash())
+    }
+
+    /// Returns `true` if every block of the side chain has been downloaded.
+    pub(crate) fn is_complete(&self) -> bool {
+        self.blocks.len() == self.block_hashes.len()
+    }
+
+    /// Returns the block heights and expected block hashes of the blocks that have not been downloaded.
+    pub(crate) fn missing_blocks(&self) -> Vec<(u32, N::BlockHash)> {
+        (self.common_ancestor + 1..)
+            .zip(self.block_hashes.iter())
+            .filter(|(block_height, _)| !self.blocks.contains_key(block_height))
+            .map(|(block_height, block_hash)| (block_height, *block_hash))
+            .collect()
+    }
+
+    /// Adds the given block to the side chain, returning `true` if it is an expected block of the side chain.
+    pub(crate) fn add_block(&mut self, block: Block<N>) -> bool {
+        match self.contains_block_hash(&block) {
+            true => {
+                self.blocks.insert(block.height(), block);
+                true
+            }
+            false => false,
+        }
+    }
+
+    /// Returns the downloaded blocks of the side chain, in increasing order of block height.
+    pub(crate) fn into_blocks(self) -> Vec<Block<N>> {
+        self.blocks.into_iter().map(|(_, block)| block).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_ledger::{storage::rocksdb::RocksDB, LedgerState};
+    use snarkvm::dpc::testnet2::Testnet2;
+
+    use rand::thread_rng;
+    use std::sync::atomic::AtomicBool;
+
+    fn temp_dir() -> std::path::PathBuf {
+        tempfile::tempdir().expect("Failed to open temporary directory").into_path()
+    }
+
+    #[test]
+    fn test_side_chain_expected_blocks() {
+        let genesis = Testnet2::genesis_block();
+        let peer_ip = "127.0.0.1:4132".parse().unwrap();
+
+        // Initialize a side chain that expects one block after the genesis block.
+        let mut side_chain = SideChain::<Testnet2>::new(peer_ip, 0, genesis.header().clone(), 1);
+        assert_eq!(0, side_chain.latest_block_height());
+        side_chain.extend(vec![(genesis.hash(), genesis.header().clone())], 2);
+        assert_eq!(1, side_chain.latest_block_height());
+        assert_eq!(2, side_chain.cumulative_work());
+        assert_eq!(Some(genesis.hash()), side_chain.get_block_hash(1));
+        assert_eq!(None, side_chain.get_block_hash(0));
+        assert_eq!(None, side_chain.get_block_hash(2));
+        assert_eq!(vec![(1, genesis.hash())], side_chain.missing_blocks());
+
+        // Ensure a block at an unexpected block height is rejected.
+        assert!(!side_chain.add_block(genesis.clone()));
+        assert!(!side_chain.is_complete());
+        assert!(side_chain.into_blocks().is_empty());
+    }
+    #[test]
+    fn test_side_chain_complete() {
+        let rng = &mut thread_rng();
+        let terminator = AtomicBool::new(false);
+        let peer_ip = "127.0.0.1:4132".parse().unwrap();
+
+        // Mine two blocks on a new ledger, which form a side chain after the genesis block.
+        let mut ledger = LedgerState::<Testnet2>::open::<RocksDB, _>(temp_dir(), false).expect("Failed to initialize ledger");
+        let address = Account::<Testnet2>::new(rng).address();
+        let mut blocks = Vec::with_capacity(2);
+        for _ in 0..2 {
+            let block = ledger.mine_next_block(address, &[], &terminator, rng).expect("Failed to mine");
+            ledger.add_next_block(&block).expect("Failed to add next block to ledger");
+            blocks.push(block);
+        }
+
+        // Initialize a side chain from the genesis block, and extend it with the block headers.
+        let genesis_work = ledger.get_cumulative_work(0).expect("Failed to get cumulative work");
+        let mut side_chain = SideChain::<Testnet2>::new(peer_ip, 0, Testnet2::genesis_block().header().clone(), genesis_work);
+        let block_headers = blocks.iter().map(|block| (block.hash(), block.header().clone())).collect();
+        side_chain.extend(block_headers, ledger.latest_cumulative_work().unwrap());
+        assert_eq!(2, side_chain.latest_block_height());
+        assert_eq!(blocks[1].header(), side_chain.latest_block_header());
+        assert_eq!(vec![(1, blocks[0].hash()), (2, blocks[1].hash())], side_chain.missing_blocks());
+
+        // Add the blocks out of order, and ensure the missing blocks shrink until the side chain is complete.
+        assert!(side_chain.add_block(blocks[1].clone()));
+        assert_eq!(vec![(1, blocks[0].hash())], side_chain.missing_blocks());
+        assert!(!side_chain.is_complete());
+        assert!(side_chain.add_block(blocks[0].clone()));
+        assert!(side_chain.missing_blocks().is_empty());
+        assert!(side_chain.is_complete());
+
+        // Ensure the blocks are returned in increasing order of block height.
+        assert_eq!(blocks, side_chain.into_blocks());
+    }
+}
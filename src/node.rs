@@ -14,21 +14,46 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{helpers::Updater, network::Server, Client, ClientTrial, Display, Environment, Miner, MinerTrial, NodeType, SyncNode};
+use crate::{
+    environment::resolve_address,
+    helpers::Updater,
+    network::Server,
+    Client,
+    ClientTrial,
+    Config,
+    Display,
+    Environment,
+    Miner,
+    MinerTrial,
+    NodeType,
+    SyncNode,
+};
+use snarkos_ledger::{storage::rocksdb::RocksDB, LedgerState};
 use snarkvm::dpc::{prelude::*, testnet2::Testnet2};
 
 use anyhow::Result;
 use colored::*;
-use std::str::FromStr;
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    str::FromStr,
+};
 use structopt::StructOpt;
+use tokio::signal;
 use tracing_subscriber::EnvFilter;
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "snarkos", author = "The Aleo Team <hello@aleo.org>", setting = structopt::clap::AppSettings::ColoredHelp)]
 pub struct Node {
-    /// Specify the IP address of a peer to connect to.
-    #[structopt(long = "connect")]
-    pub connect: Option<String>,
+    /// Specify the path to a TOML configuration file for the node.
+    #[structopt(long = "config", parse(from_os_str))]
+    pub config: Option<PathBuf>,
+    /// Specify the directory for storing the ledger and other node state.
+    #[structopt(long = "data-dir", parse(from_os_str))]
+    pub data_dir: Option<PathBuf>,
+    /// Specify the addresses of peers to connect to, as a comma-separated list of `host:port` entries.
+    #[structopt(long = "connect", use_delimiter = true)]
+    pub connect: Vec<String>,
     /// Specify this as a mining node, with the given miner address.
     #[structopt(long = "miner")]
     pub miner: Option<String>,
@@ -38,15 +63,27 @@ pub struct Node {
     /// Specify the port for the node server.
     #[structopt(long = "node")]
     pub node: Option<u16>,
+    /// Specify the IP address to bind the node server to.
+    #[structopt(long = "node-bind")]
+    pub node_bind: Option<IpAddr>,
+    /// Specify the address advertised to peers, if it differs from the node server address.
+    #[structopt(long = "external-addr")]
+    pub external_addr: Option<SocketAddr>,
     /// Specify the port for the RPC server.
     #[structopt(long = "rpc")]
     pub rpc: Option<u16>,
+    /// Specify the IP address to bind the RPC server to.
+    #[structopt(long = "rpc-bind")]
+    pub rpc_bind: Option<IpAddr>,
     /// Specify the username for the RPC server.
     #[structopt(default_value = "root", long = "username")]
     pub rpc_username: String,
-    /// Specify the password for the RPC server.
-    #[structopt(default_value = "pass", long = "password")]
-    pub rpc_password: String,
+    /// Specify the password for the RPC server. If unset, a random password is written to the `.cookie` file in the data directory.
+    #[structopt(long = "password")]
+    pub rpc_password: Option<String>,
+    /// Specify the number of threads used to mine blocks.
+    #[structopt(long = "mining-threads")]
+    pub mining_threads: Option<usize>,
     /// Specify the verbosity of the node [options: 0, 1, 2, 3]
     #[structopt(default_value = "3", long = "verbosity")]
     pub verbosity: u8,
@@ -83,12 +120,8 @@ impl Node {
     }
 
     async fn start_server<N: Network, E: Environment>(&self) -> Result<()> {
-        let node_port = self.node.unwrap_or(E::DEFAULT_NODE_PORT);
-        let rpc_port = self.rpc.unwrap_or(E::DEFAULT_RPC_PORT);
-        assert!(
-            !(node_port < 4130),
-            "Until configuration files are established, the node port must be at least 4130 or greater"
-        );
+        let config = self.load_config::<E>()?;
+        let peer_nodes = config.peer_nodes.clone();
 
         let miner = match (E::NODE_TYPE, &self.miner) {
             (NodeType::Miner, Some(address)) => {
@@ -107,25 +140,90 @@ impl Node {
 
         if self.display {
             println!("\nThe snarkOS console is initializing...\n");
-            let server =
-                Server::<N, E>::initialize(node_port, rpc_port, self.rpc_username.clone(), self.rpc_password.clone(), miner).await?;
-            if let Some(peer_ip) = &self.connect {
-                server.connect_to(peer_ip.parse().unwrap()).await?;
-            }
-            let _display = Display::<N, E>::start(server)?;
-            Ok(())
+            let server = Server::<N, E>::initialize(config, self.rpc_username.clone(), self.rpc_password.clone(), miner).await?;
+            Self::connect_to_peer_nodes(&server, peer_nodes).await;
+            // Render the display until it is closed, and proceed to gracefully shut down the node.
+            let result = Display::<N, E>::start(&server);
+            server.shut_down().await;
+            result
         } else {
             self.initialize_logger();
-            let server =
-                Server::<N, E>::initialize(node_port, rpc_port, self.rpc_username.clone(), self.rpc_password.clone(), miner).await?;
-            if let Some(peer_ip) = &self.connect {
-                server.connect_to(peer_ip.parse().unwrap()).await?;
-            }
-            std::future::pending::<()>().await;
+            let server = Server::<N, E>::initialize(config, self.rpc_username.clone(), self.rpc_password.clone(), miner).await?;
+            Self::connect_to_peer_nodes(&server, peer_nodes).await;
+            // Wait for a shutdown signal, and proceed to gracefully shut down the node.
+            Self::wait_for_shutdown_signal().await?;
+            server.shut_down().await;
             Ok(())
         }
     }
 
+    /// Sends a connection request to each of the given peer nodes, logging any failure instead of aborting startup.
+    async fn connect_to_peer_nodes<N: Network, E: Environment>(server: &Server<N, E>, peer_nodes: Vec<SocketAddr>) {
+        for peer_ip in peer_nodes {
+            if let Err(error) = server.connect_to(peer_ip).await {
+                warn!("Failed to connect to peer {}: {}", peer_ip, error);
+            }
+        }
+    }
+
+    /// Returns the node configuration, layering the config file (if given) over the environment defaults,
+    /// and the CLI flags over the config file.
+    fn load_config<E: Environment>(&self) -> Result<Config> {
+        let mut config = match &self.config {
+            Some(path) => Config::load::<E, _>(path)?,
+            None => Config::new::<E>(),
+        };
+
+        // Override the config with the CLI flags, if they are set.
+        if let Some(data_dir) = &self.data_dir {
+            config.data_dir = data_dir.clone();
+        }
+        if let Some(node_bind) = self.node_bind {
+            config.node_bind = node_bind;
+        }
+        if let Some(node_port) = self.node {
+            config.node_port = node_port;
+        }
+        if let Some(rpc_bind) = self.rpc_bind {
+            config.rpc_bind = rpc_bind;
+        }
+        if let Some(rpc_port) = self.rpc {
+            config.rpc_port = rpc_port;
+        }
+        if let Some(external_addr) = self.external_addr {
+            config.external_addr = Some(external_addr);
+        }
+        if let Some(mining_threads) = self.mining_threads {
+            config.mining_threads = mining_threads;
+        }
+        // Add the peers to connect to into the list of bootstrap peer nodes.
+        for address in &self.connect {
+            let peer_ip = resolve_address(address)?;
+            if !config.peer_nodes.contains(&peer_ip) {
+                config.peer_nodes.push(peer_ip);
+            }
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Waits for a SIGINT or SIGTERM signal.
+    async fn wait_for_shutdown_signal() -> Result<()> {
+        #[cfg(unix)]
+        {
+            let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())?;
+            tokio::select! {
+                result = signal::ctrl_c() => result?,
+                _ = terminate.recv() => (),
+            }
+        }
+        #[cfg(not(unix))]
+        signal::ctrl_c().await?;
+
+        Ok(())
+    }
+
     fn initialize_logger(&self) {
         match self.verbosity {
             0 => std::env::set_var("RUST_LOG", "info"),
@@ -157,6 +255,8 @@ pub enum Command {
     Update(Update),
     #[structopt(name = "experimental", about = "Experimental features")]
     Experimental(Experimental),
+    #[structopt(name = "ledger", about = "Manages the ledger storage")]
+    Ledger(LedgerCommand),
 }
 
 impl Command {
@@ -164,6 +264,7 @@ impl Command {
         match self {
             Self::Update(command) => command.parse(),
             Self::Experimental(command) => command.parse(),
+            Self::Ledger(command) => command.parse(),
         }
     }
 }
@@ -249,3 +350,54 @@ impl NewAccount {
         Ok(output)
     }
 }
+
+#[derive(StructOpt, Debug)]
+pub struct LedgerCommand {
+    #[structopt(subcommand)]
+    commands: LedgerCommands,
+}
+
+impl LedgerCommand {
+    pub fn parse(self) -> Result<String> {
+        match self.commands {
+            LedgerCommands::Repair(command) => command.parse(),
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+pub enum LedgerCommands {
+    #[structopt(name = "repair", about = "Repairs inconsistent ledger storage")]
+    Repair(Repair),
+}
+
+#[derive(StructOpt, Debug)]
+pub struct Repair {
+    /// Specify the path to the TOML configuration file of the node
+    #[structopt(long = "config", parse(from_os_str))]
+    config: Option<PathBuf>,
+    /// Specify the directory for storing the ledger and other node state
+    #[structopt(long = "data-dir", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+}
+
+impl Repair {
+    pub fn parse(self) -> Result<String> {
+        // Resolve the data directory in the same way as the node, so the repair runs on the ledger of the node.
+        let node = Node {
+            config: self.config,
+            data_dir: self.data_dir,
+            ..Node::from_iter(&["snarkos"])
+        };
+        let storage_path = node.load_config::<Client<Testnet2>>()?.data_dir.join("ledger");
+
+        // The node must be stopped, as the ledger storage cannot be opened by more than one writer.
+        let ledger = LedgerState::<Testnet2>::repair::<RocksDB, _>(&storage_path)?;
+
+        Ok(format!(
+            "\nRepaired the ledger in {} at block {}\n",
+            storage_path.display(),
+            ledger.latest_block_height()
+        ))
+    }
+}
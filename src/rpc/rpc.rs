@@ -17,14 +17,21 @@
 //! Logic for instantiating the RPC server.
 
 use crate::{
-    rpc::{rpc_impl::RpcImpl, rpc_trait::RpcFunctions},
+    ledger::Ledger,
+    rpc::{
+        rpc_impl::RpcImpl,
+        rpc_trait::{ProtectedRpcFunctions, RpcFunctions},
+    },
     Environment,
     LedgerRouter,
     Peers,
+    PeersRouter,
+    TransactionError,
 };
 use snarkos_ledger::LedgerState;
 use snarkvm::dpc::Network;
 
+use anyhow::{anyhow, Result};
 use hyper::{
     body::HttpBody,
     server::{conn::AddrStream, Server},
@@ -33,8 +40,16 @@ use hyper::{
 };
 use json_rpc_types as jrt;
 use jsonrpc_core::{Metadata, Params};
+use rand::{thread_rng, Rng};
 use serde::{Deserialize, Serialize};
-use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+use std::{
+    convert::Infallible,
+    fs::{self, OpenOptions},
+    io::Write,
+    net::SocketAddr,
+    path::Path,
+    sync::Arc,
+};
 use tokio::sync::RwLock;
 
 /// Defines the authentication format for accessing private endpoints on the RPC server.
@@ -46,6 +61,52 @@ pub struct RpcCredentials {
     pub password: String,
 }
 
+impl RpcCredentials {
+    /// Generates credentials with a random password for the given username, and writes them to the given cookie file.
+    pub fn from_cookie_file<P: AsRef<Path>>(path: P, username: String) -> Result<Self> {
+        let path = path.as_ref();
+        let password = hex::encode(thread_rng().gen::<[u8; 32]>());
+
+        // Ensure the cookie file is only readable by the owner, before the credentials are written to it.
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let write_cookie_file = || -> Result<()> {
+            let mut file = options.open(path)?;
+            // The mode only applies to a new file, so restrict the permissions of an existing file as well.
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                file.set_permissions(fs::Permissions::from_mode(0o600))?;
+            }
+            file.write_all(format!("{}:{}", username, password).as_bytes())?;
+            Ok(())
+        };
+        write_cookie_file().map_err(|error| anyhow!("Failed to write the RPC cookie file {}: {}", path.display(), error))?;
+
+        Ok(Self { username, password })
+    }
+
+    /// Returns `true` if the given `Authorization` header holds these credentials, using HTTP Basic authentication.
+    pub fn is_authorized(&self, auth: Option<&str>) -> bool {
+        let encoded = match auth.and_then(|auth| auth.strip_prefix("Basic ")) {
+            Some(encoded) => encoded.trim(),
+            None => return false,
+        };
+        let decoded = match base64::decode(encoded) {
+            Ok(decoded) => decoded,
+            Err(_) => return false,
+        };
+        let expected = format!("{}:{}", self.username, self.password);
+        // Compare in constant time, to avoid leaking the credentials through timing.
+        decoded.len() == expected.len() && decoded.iter().zip(expected.as_bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+    }
+}
+
 /// RPC metadata for encoding authentication.
 #[derive(Default, Clone)]
 pub struct Meta {
@@ -55,7 +116,19 @@ pub struct Meta {
 
 impl Metadata for Meta {}
 
-const METHODS_EXPECTING_PARAMS: [&str; 12] = [
+const PRIVATE_METHODS: [&str; 9] = [
+    "connect",
+    "disconnect",
+    "banpeer",
+    "unbanpeer",
+    "listbanned",
+    "startmining",
+    "stopmining",
+    "setminingrecipient",
+    "setminingthreads",
+];
+
+const METHODS_EXPECTING_PARAMS: [&str; 21] = [
     // public
     "getblock",
     "getblocks",
@@ -68,30 +141,43 @@ const METHODS_EXPECTING_PARAMS: [&str; 12] = [
     "getledgerproof",
     "gettransaction",
     "gettransition",
+    "getunconfirmedtransaction",
     "sendtransaction",
-    // "validaterawtransaction",
-    // // private
+    "submitblock",
+    "validaterawtransaction",
+    // private
+    "connect",
+    "disconnect",
+    "banpeer",
+    "unbanpeer",
+    "setminingrecipient",
+    "setminingthreads",
     // "createrawtransaction",
     // "createtransaction",
     // "getrawrecord",
     // "decoderecord",
     // "decryptrecord",
-    // "disconnect",
-    // "connect",
 ];
 
 /// Starts a local RPC HTTP server at `rpc_port` in a dedicated `tokio` task.
 /// RPC failures do not affect the rest of the node.
 pub fn initialize_rpc_server<N: Network, E: Environment>(
     rpc_addr: SocketAddr,
-    username: String,
-    password: String,
+    credentials: RpcCredentials,
     peers: &Arc<RwLock<Peers<N, E>>>,
+    peers_router: &PeersRouter<N, E>,
     ledger: LedgerState<N>,
+    network_ledger: &Arc<RwLock<Ledger<N, E>>>,
     ledger_router: &LedgerRouter<N, E>,
 ) -> tokio::task::JoinHandle<()> {
-    let credentials = RpcCredentials { username, password };
-    let rpc_impl = RpcImpl::new(credentials, peers.clone(), ledger, ledger_router.clone());
+    let rpc_impl = RpcImpl::new(
+        credentials,
+        peers.clone(),
+        peers_router.clone(),
+        ledger,
+        network_ledger.clone(),
+        ledger_router.clone(),
+    );
 
     let service = make_service_fn(move |conn: &AddrStream| {
         let caller = conn.remote_addr();
@@ -116,7 +202,7 @@ async fn handle_rpc<N: Network, E: Environment>(
         .headers()
         .get(hyper::header::AUTHORIZATION)
         .map(|h| h.to_str().unwrap_or("").to_owned());
-    let _meta = Meta { auth };
+    let meta = Meta { auth };
 
     // Save the headers.
     let headers = req.headers().clone();
@@ -155,6 +241,25 @@ async fn handle_rpc<N: Network, E: Environment>(
 
     debug!("Received '{}' RPC request from {}: {:?}", &*req.method, caller, headers);
 
+    // Ensure the caller is authorized to call private methods.
+    if PRIVATE_METHODS.contains(&&*req.method) && !rpc.credentials.is_authorized(meta.auth.as_deref()) {
+        warn!("Unauthorized '{}' RPC request from {}", &*req.method, caller);
+        let resp = jrt::Response::<(), ()>::error(
+            jrt::Version::V2,
+            jrt::Error::with_custom_msg(jrt::ErrorCode::ServerError(-32001), "Unauthorized"),
+            req.id.clone(),
+        );
+        let body = serde_json::to_vec(&resp).unwrap_or_default();
+
+        let mut response = hyper::Response::new(body.into());
+        *response.status_mut() = hyper::StatusCode::UNAUTHORIZED;
+        response.headers_mut().insert(
+            hyper::header::WWW_AUTHENTICATE,
+            hyper::header::HeaderValue::from_static("Basic realm=\"snarkOS\""),
+        );
+        return Ok(response);
+    }
+
     // Read the request params.
     let mut params = match read_params(&req) {
         Ok(params) => params,
@@ -293,6 +398,48 @@ async fn handle_rpc<N: Network, E: Environment>(
             let result = rpc.get_connected_peers().await.map_err(convert_crate_err);
             result_to_response(&req, result)
         }
+        "getblocktemplate" => {
+            let result = rpc.get_block_template().await.map_err(convert_crate_err);
+            result_to_response(&req, result)
+        }
+        "submitblock" => {
+            let result = rpc
+                .submit_block(params[0].as_str().unwrap_or("").into())
+                .await
+                .map_err(convert_crate_err);
+            result_to_response(&req, result)
+        }
+        "validaterawtransaction" => {
+            let result = rpc
+                .validate_raw_transaction(params[0].as_str().unwrap_or("").into())
+                .await
+                .map_err(convert_crate_err);
+            result_to_response(&req, result)
+        }
+        "getmemorypool" => {
+            let result = rpc.get_memory_pool().await.map_err(convert_crate_err);
+            result_to_response(&req, result)
+        }
+        "getminingstats" => {
+            let result = rpc.get_mining_stats().await.map_err(convert_crate_err);
+            result_to_response(&req, result)
+        }
+        "getmemorypoolinfo" => {
+            let result = rpc.get_memory_pool_info().await.map_err(convert_crate_err);
+            result_to_response(&req, result)
+        }
+        "getunconfirmedtransaction" => {
+            let result = rpc.get_unconfirmed_transaction(params.remove(0)).await.map_err(convert_crate_err);
+            result_to_response(&req, result)
+        }
+        "getnodeinfo" => {
+            let result = rpc.get_node_info().await.map_err(convert_crate_err);
+            result_to_response(&req, result)
+        }
+        "getsyncstatus" => {
+            let result = rpc.get_sync_status().await.map_err(convert_crate_err);
+            result_to_response(&req, result)
+        }
         "sendtransaction" => {
             let result = rpc
                 .send_transaction(params[0].as_str().unwrap_or("").into())
@@ -300,6 +447,91 @@ async fn handle_rpc<N: Network, E: Environment>(
                 .map_err(convert_crate_err);
             result_to_response(&req, result)
         }
+        // Private
+        "connect" => match params
+            .into_iter()
+            .map(serde_json::from_value::<SocketAddr>)
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(peer_ips) => {
+                let result = rpc.connect(peer_ips).await.map_err(convert_crate_err);
+                result_to_response(&req, result)
+            }
+            Err(_) => {
+                let err = jrt::Error::with_custom_msg(jrt::ErrorCode::ParseError, "Invalid peer address!");
+                jrt::Response::error(jrt::Version::V2, err, req.id.clone())
+            }
+        },
+        "disconnect" => match serde_json::from_value::<SocketAddr>(params.remove(0)) {
+            Ok(peer_ip) => {
+                let result = rpc.disconnect(peer_ip).await.map_err(convert_crate_err);
+                result_to_response(&req, result)
+            }
+            Err(_) => {
+                let err = jrt::Error::with_custom_msg(jrt::ErrorCode::ParseError, "Invalid peer address!");
+                jrt::Response::error(jrt::Version::V2, err, req.id.clone())
+            }
+        },
+        "banpeer" => {
+            let peer_ip = serde_json::from_value::<SocketAddr>(params.remove(0));
+            let duration_in_secs = match params.is_empty() {
+                true => Ok(None),
+                false => serde_json::from_value::<u64>(params.remove(0)).map(Some),
+            };
+            match (peer_ip, duration_in_secs) {
+                (Ok(peer_ip), Ok(duration_in_secs)) => {
+                    let result = rpc.ban_peer(peer_ip, duration_in_secs).await.map_err(convert_crate_err);
+                    result_to_response(&req, result)
+                }
+                (Err(_), _) => {
+                    let err = jrt::Error::with_custom_msg(jrt::ErrorCode::ParseError, "Invalid peer address!");
+                    jrt::Response::error(jrt::Version::V2, err, req.id.clone())
+                }
+                (_, Err(_)) => {
+                    let err = jrt::Error::with_custom_msg(jrt::ErrorCode::ParseError, "Invalid ban duration!");
+                    jrt::Response::error(jrt::Version::V2, err, req.id.clone())
+                }
+            }
+        }
+        "startmining" => {
+            let result = rpc.start_mining().await.map_err(convert_crate_err);
+            result_to_response(&req, result)
+        }
+        "stopmining" => {
+            let result = rpc.stop_mining().await.map_err(convert_crate_err);
+            result_to_response(&req, result)
+        }
+        "setminingrecipient" => {
+            let result = rpc
+                .set_mining_recipient(params[0].as_str().unwrap_or("").into())
+                .await
+                .map_err(convert_crate_err);
+            result_to_response(&req, result)
+        }
+        "setminingthreads" => match serde_json::from_value::<usize>(params.remove(0)) {
+            Ok(number_of_threads) => {
+                let result = rpc.set_mining_threads(number_of_threads).await.map_err(convert_crate_err);
+                result_to_response(&req, result)
+            }
+            Err(_) => {
+                let err = jrt::Error::with_custom_msg(jrt::ErrorCode::ParseError, "Invalid number of threads!");
+                jrt::Response::error(jrt::Version::V2, err, req.id.clone())
+            }
+        },
+        "unbanpeer" => match serde_json::from_value::<SocketAddr>(params.remove(0)) {
+            Ok(peer_ip) => {
+                let result = rpc.unban_peer(peer_ip).await.map_err(convert_crate_err);
+                result_to_response(&req, result)
+            }
+            Err(_) => {
+                let err = jrt::Error::with_custom_msg(jrt::ErrorCode::ParseError, "Invalid peer address!");
+                jrt::Response::error(jrt::Version::V2, err, req.id.clone())
+            }
+        },
+        "listbanned" => {
+            let result = rpc.list_banned().await.map_err(convert_crate_err);
+            result_to_response(&req, result)
+        }
         // "decoderawtransaction" => {
         //     let result = rpc
         //         .decode_raw_transaction(params[0].as_str().unwrap_or("").into())
@@ -368,20 +600,6 @@ async fn handle_rpc<N: Network, E: Environment>(
         //         .map_err(convert_core_err);
         //     result_to_response(&req, result)
         // }
-        // "disconnect" => {
-        //     let result = rpc
-        //         .disconnect_protected(Params::Array(params), meta)
-        //         .await
-        //         .map_err(convert_core_err);
-        //     result_to_response(&req, result)
-        // }
-        // "connect" => {
-        //     let result = rpc
-        //         .connect_protected(Params::Array(params), meta)
-        //         .await
-        //         .map_err(convert_core_err);
-        //     result_to_response(&req, result)
-        // }
         _ => {
             let err = jrt::Error::from_code(jrt::ErrorCode::MethodNotFound);
             jrt::Response::error(jrt::Version::V2, err, req.id.clone())
@@ -410,10 +628,36 @@ fn read_params(req: &jrt::Request<Params>) -> Result<Vec<serde_json::Value>, jrt
 
 /// Converts the crate's RpcError into a jrt::RpcError
 fn convert_crate_err(err: crate::rpc::rpc_impl::RpcError) -> jrt::Error<String> {
+    if let crate::rpc::rpc_impl::RpcError::Transaction(error) = &err {
+        return convert_transaction_err(error);
+    }
     let error = jrt::Error::with_custom_msg(jrt::ErrorCode::ServerError(-32000), "internal error");
     error.set_data(err.to_string())
 }
 
+/// Converts the ledger's TransactionError into a jrt::RpcError, with a distinct error code for each rejection reason
+fn convert_transaction_err(err: &TransactionError) -> jrt::Error<String> {
+    let (code, message) = transaction_error_code(err);
+    let error = jrt::Error::with_custom_msg(jrt::ErrorCode::ServerError(code), message);
+    error.set_data(err.to_string())
+}
+
+/// Returns the error code and short message for the given TransactionError
+pub(crate) fn transaction_error_code(err: &TransactionError) -> (i64, &'static str) {
+    match err {
+        TransactionError::Invalid(..) => (-32010, "invalid transaction"),
+        TransactionError::AlreadyInLedger(..) => (-32011, "transaction already in ledger"),
+        TransactionError::AlreadyInMemoryPool(..) => (-32012, "transaction already in memory pool"),
+        TransactionError::UnknownLedgerRoot(..) => (-32013, "unknown ledger root"),
+        TransactionError::SpentSerialNumber(..) => (-32014, "serial number already spent"),
+        TransactionError::ConflictingSerialNumber(..) => (-32015, "serial number conflicts with memory pool"),
+        TransactionError::Rejected(..) => (-32016, "transaction rejected by memory pool"),
+        TransactionError::DuplicateCommitment(..) => (-32017, "commitment already exists"),
+        TransactionError::ConflictingCommitment(..) => (-32018, "commitment conflicts with memory pool"),
+        TransactionError::Storage(..) => (-32000, "internal error"),
+    }
+}
+
 /// Converts the jsonrpc-core's Error into a jrt::RpcError
 #[allow(unused)]
 fn convert_core_err(err: jsonrpc_core::Error) -> jrt::Error<String> {
@@ -437,7 +681,7 @@ fn result_to_response<T: Serialize>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{ledger::Ledger, Client};
+    use crate::{ledger::Status, Client, Config, LedgerRequest, Miner, MiningHistory, NodeType, PeerBook, PeersRequest};
 
     use snarkos_ledger::{
         storage::{rocksdb::RocksDB, Storage},
@@ -452,7 +696,7 @@ mod tests {
     use hyper::Request;
     use rand::{thread_rng, SeedableRng};
     use rand_chacha::ChaChaRng;
-    use std::{str::FromStr, sync::atomic::AtomicBool};
+    use std::{str::FromStr, sync::atomic::AtomicBool, time::Duration};
     use tokio::sync::mpsc;
 
     fn temp_dir() -> std::path::PathBuf {
@@ -471,12 +715,16 @@ mod tests {
 
     /// Initializes a new instance of the ledger.
     fn new_ledger<N: Network, E: Environment, S: Storage>() -> Ledger<N, E> {
-        Ledger::<N, E>::open::<S, _>(temp_dir()).expect("Failed to initialize ledger")
+        let peer_book = PeerBook::open::<S, _>(temp_dir()).expect("Failed to initialize peer book");
+        let mining_history = MiningHistory::open::<S, _>(temp_dir()).expect("Failed to initialize mining history");
+        Ledger::<N, E>::open::<S, _>(temp_dir(), Config::new::<E>(), peer_book, mining_history).expect("Failed to initialize ledger")
     }
 
     /// Initializes a new instance of the Peers.
     fn new_peers<N: Network, E: Environment>() -> Arc<RwLock<Peers<N, E>>> {
-        Arc::new(RwLock::new(Peers::new("0.0.0.0:4130".parse().unwrap(), None)))
+        let local_ip = "0.0.0.0:4130".parse().unwrap();
+        let peer_book = PeerBook::open::<RocksDB, _>(temp_dir()).expect("Failed to initialize peer book");
+        Arc::new(RwLock::new(Peers::new(local_ip, None, Config::new::<E>(), peer_book)))
     }
 
     /// Initializes a new instance of the rpc.
@@ -487,11 +735,47 @@ mod tests {
         };
         let peers = new_peers::<N, E>();
         let ledger = new_ledger_state::<N, S>();
+        let network_ledger = Arc::new(RwLock::new(new_ledger::<N, E, S>()));
 
+        // Create a dummy mpsc channel for Peers requests.
+        let (peers_router, _peers_handler) = mpsc::channel(1024);
         // Create a dummy mpsc channel for Ledger requests. todo (@collinc97): only get requests will work until this is changed
         let (ledger_router, _ledger_handler) = mpsc::channel(1024);
 
-        RpcImpl::<N, E>::new(credentials, peers, ledger, ledger_router)
+        RpcImpl::<N, E>::new(credentials, peers, peers_router, ledger, network_ledger, ledger_router)
+    }
+
+    /// Initializes a new instance of the rpc, with a handler that replies to `SendTransaction` requests using the given closure.
+    fn new_rpc_impl_with_ledger_handler<N: Network, E: Environment, S: Storage>(
+        handler: fn(&Transaction<N>) -> Result<N::TransactionID, TransactionError>,
+    ) -> RpcImpl<N, E> {
+        let credentials = RpcCredentials {
+            username: "root".to_string(),
+            password: "pass".to_string(),
+        };
+        let peers = new_peers::<N, E>();
+        let ledger = new_ledger_state::<N, S>();
+        let network_ledger = Arc::new(RwLock::new(new_ledger::<N, E, S>()));
+        let (peers_router, _peers_handler) = mpsc::channel(1024);
+        let (ledger_router, mut ledger_handler) = mpsc::channel(1024);
+
+        tokio::spawn(async move {
+            while let Some(request) = ledger_handler.recv().await {
+                if let LedgerRequest::SendTransaction(_, transaction, responder) = request {
+                    let _ = responder.send(handler(&transaction));
+                }
+            }
+        });
+
+        RpcImpl::<N, E>::new(credentials, peers, peers_router, ledger, network_ledger, ledger_router)
+    }
+
+    /// Initializes a new request with the given body, authorized with the default credentials.
+    fn new_authorized_request(body: &'static str) -> Request<Body> {
+        Request::builder()
+            .header(hyper::header::AUTHORIZATION, format!("Basic {}", base64::encode("root:pass")))
+            .body(Body::from(body))
+            .unwrap()
     }
 
     /// Deserializes a rpc response into the given type.
@@ -747,10 +1031,12 @@ mod tests {
 
             // Open a ledger at the temporary directory.
             let ledger = LedgerState::open::<RocksDB, _>(directory, false).expect("Failed to initialize ledger");
+            let network_ledger = Arc::new(RwLock::new(new_ledger::<Testnet2, Client<Testnet2>, RocksDB>()));
             let (ledger_router, _ledger_handler) = mpsc::channel(1024);
             let peers = new_peers();
+            let (peers_router, _peers_handler) = mpsc::channel(1024);
 
-            RpcImpl::<Testnet2, Client<Testnet2>>::new(credentials, peers, ledger, ledger_router)
+            RpcImpl::<Testnet2, Client<Testnet2>>::new(credentials, peers, peers_router, ledger, network_ledger, ledger_router)
         };
 
         // Initialize a new request that calls the `getblocks` endpoint.
@@ -873,12 +1159,14 @@ mod tests {
                 password: "pass".to_string(),
             };
             let peers = new_peers();
+            let (peers_router, _peers_handler) = mpsc::channel(1024);
 
             // Open a ledger at the temporary directory.
             let ledger = LedgerState::<Testnet2>::open::<RocksDB, _>(directory, false).expect("Failed to initialize ledger");
+            let network_ledger = Arc::new(RwLock::new(new_ledger::<Testnet2, Client<Testnet2>, RocksDB>()));
             let (ledger_router, _ledger_handler) = mpsc::channel(1024);
 
-            RpcImpl::<Testnet2, Client<Testnet2>>::new(credentials, peers, ledger, ledger_router)
+            RpcImpl::<Testnet2, Client<Testnet2>>::new(credentials, peers, peers_router, ledger, network_ledger, ledger_router)
         };
 
         // Initialize a new request that calls the `getblockhashes` endpoint.
@@ -1045,12 +1333,14 @@ mod tests {
                 password: "pass".to_string(),
             };
             let peers = new_peers();
+            let (peers_router, _peers_handler) = mpsc::channel(1024);
 
             // Open a ledger at the temporary directory.
             let ledger = LedgerState::<Testnet2>::open::<RocksDB, _>(directory, false).expect("Failed to initialize ledger");
+            let network_ledger = Arc::new(RwLock::new(new_ledger::<Testnet2, Client<Testnet2>, RocksDB>()));
             let (ledger_router, _ledger_handler) = mpsc::channel(1024);
 
-            RpcImpl::<Testnet2, Client<Testnet2>>::new(credentials, peers, ledger, ledger_router)
+            RpcImpl::<Testnet2, Client<Testnet2>>::new(credentials, peers, peers_router, ledger, network_ledger, ledger_router)
         };
 
         // Initialize a new request that calls the `getledgerproof` endpoint.
@@ -1190,6 +1480,167 @@ mod tests {
         assert_eq!(actual, Vec::<String>::new());
     }
 
+    #[tokio::test]
+    async fn test_get_node_info() {
+        // Initialize a new rpc.
+        let rpc = new_rpc_impl::<Testnet2, Client<Testnet2>, RocksDB>();
+
+        // Initialize a new request that calls the `getnodeinfo` endpoint.
+        let request = Request::new(Body::from(
+            r#"{
+	"jsonrpc": "2.0",
+	"id": "1",
+	"method": "getnodeinfo",
+	"params": []
+}"#,
+        ));
+
+        // Send the request to the rpc.
+        let response = handle_rpc(caller(), rpc, request)
+            .await
+            .expect("Test rpc failed to process request");
+
+        // Process the response into the node info.
+        let actual: serde_json::Value = process_response(response).await;
+
+        // Check the node info.
+        assert_eq!(actual["node_type"], serde_json::json!(NodeType::Client));
+        assert_eq!(actual["status"], serde_json::json!(Status::Peering));
+        assert_eq!(actual["message_version"], <Client<Testnet2> as Environment>::MESSAGE_VERSION);
+        assert_eq!(actual["latest_block_height"], 0);
+        assert_eq!(actual["number_of_connected_peers"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_sync_status() {
+        // Initialize a new rpc.
+        let rpc = new_rpc_impl::<Testnet2, Client<Testnet2>, RocksDB>();
+
+        // Initialize a new request that calls the `getsyncstatus` endpoint.
+        let request = Request::new(Body::from(
+            r#"{
+	"jsonrpc": "2.0",
+	"id": "1",
+	"method": "getsyncstatus",
+	"params": []
+}"#,
+        ));
+
+        // Send the request to the rpc.
+        let response = handle_rpc(caller(), rpc, request)
+            .await
+            .expect("Test rpc failed to process request");
+
+        // Process the response into the sync status.
+        let actual: serde_json::Value = process_response(response).await;
+
+        // Check the sync status.
+        assert_eq!(actual["is_syncing"], false);
+        assert_eq!(actual["latest_block_height"], 0);
+        assert!(actual["latest_peer_block_height"].is_null());
+        assert_eq!(actual["block_requests"], 0);
+        assert_eq!(actual["peers"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_get_memory_pool_info() {
+        // Initialize a new rpc.
+        let rpc = new_rpc_impl::<Testnet2, Client<Testnet2>, RocksDB>();
+
+        // Initialize a new request that calls the `getmemorypoolinfo` endpoint.
+        let request = Request::new(Body::from(
+            r#"{
+	"jsonrpc": "2.0",
+	"id": "1",
+	"method": "getmemorypoolinfo",
+	"params": []
+}"#,
+        ));
+
+        // Send the request to the rpc.
+        let response = handle_rpc(caller(), rpc, request)
+            .await
+            .expect("Test rpc failed to process request");
+
+        // Process the response into the memory pool info.
+        let actual: serde_json::Value = process_response(response).await;
+
+        // Check the memory pool is empty.
+        assert_eq!(actual, serde_json::json!({ "size": 0, "bytes": 0 }));
+    }
+
+    #[tokio::test]
+    async fn test_get_mining_stats() {
+        // Initialize a new ledger.
+        let network_ledger = new_ledger::<Testnet2, Client<Testnet2>, RocksDB>();
+
+        // Record the genesis block as mined by this node, and add it to the canonical chain.
+        let block = Testnet2::genesis_block();
+        let mining_history = network_ledger.mining_history();
+        mining_history.add_mined_block(block, Duration::from_millis(1500)).unwrap();
+        mining_history.set_canonical(block.height(), &block.hash()).unwrap();
+
+        // Initialize a new rpc with the ledger.
+        let credentials = RpcCredentials {
+            username: "root".to_string(),
+            password: "pass".to_string(),
+        };
+        let peers = new_peers::<Testnet2, Client<Testnet2>>();
+        let ledger = new_ledger_state::<Testnet2, RocksDB>();
+        let network_ledger = Arc::new(RwLock::new(network_ledger));
+        let (peers_router, _peers_handler) = mpsc::channel(1024);
+        let (ledger_router, _ledger_handler) = mpsc::channel(1024);
+        let rpc = RpcImpl::<Testnet2, Client<Testnet2>>::new(credentials, peers, peers_router, ledger, network_ledger, ledger_router);
+
+        // Initialize a new request that calls the `getminingstats` endpoint.
+        let request = Request::new(Body::from(
+            r#"{
+	"jsonrpc": "2.0",
+	"id": "1",
+	"method": "getminingstats",
+	"params": []
+}"#,
+        ));
+
+        // Send the request to the rpc.
+        let response = handle_rpc(caller(), rpc, request)
+            .await
+            .expect("Test rpc failed to process request");
+
+        // Process the response into the mining stats.
+        let actual: serde_json::Value = process_response(response).await;
+
+        // Check the mined block is reported as canonical.
+        assert_eq!(actual["is_mining"], false);
+        assert_eq!(actual["mining_recipient"], serde_json::Value::Null);
+        assert_eq!(actual["stats"]["blocks_mined"], 1);
+        assert_eq!(actual["stats"]["blocks_canonical"], 1);
+        assert_eq!(actual["stats"]["total_reward"], Block::<Testnet2>::block_reward(block.height()).0);
+        assert_eq!(actual["stats"]["average_time_in_ms"], 1500);
+        assert_eq!(actual["blocks"][0]["block_hash"], serde_json::json!(block.hash()));
+        assert_eq!(actual["blocks"][0]["status"], "Canonical");
+    }
+
+    #[tokio::test]
+    async fn test_get_unconfirmed_transaction() {
+        let mut rng = ChaChaRng::seed_from_u64(123456789);
+
+        // Initialize a new rpc.
+        let rpc = new_rpc_impl::<Testnet2, Client<Testnet2>, RocksDB>();
+
+        // Check the memory pool is empty.
+        assert!(rpc.get_memory_pool().await.unwrap().is_empty());
+
+        // Initialize a new transaction that is not in the memory pool.
+        let address = Account::<Testnet2>::new(&mut rng).address();
+        let transaction =
+            Transaction::<Testnet2>::new_coinbase(address, AleoAmount(1234), &mut rng).expect("Failed to create a coinbase transaction");
+
+        // Check the transaction is not found.
+        let transaction_id = serde_json::json!(transaction.transaction_id());
+        assert!(rpc.get_unconfirmed_transaction(transaction_id).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_send_transaction() {
         let mut rng = ChaChaRng::seed_from_u64(123456789);
@@ -1202,8 +1653,8 @@ mod tests {
         let transaction =
             Transaction::<Testnet2>::new_coinbase(address, AleoAmount(1234), &mut rng).expect("Failed to create a coinbase transaction");
 
-        // Initialize a new rpc.
-        let rpc = new_rpc_impl::<Testnet2, Client<Testnet2>, RocksDB>();
+        // Initialize a new rpc, with a handler for Ledger requests that accepts the transaction.
+        let rpc = new_rpc_impl_with_ledger_handler::<Testnet2, Client<Testnet2>, RocksDB>(|transaction| Ok(transaction.transaction_id()));
 
         // Initialize a new request that calls the `sendtransaction` endpoint.
         let request = Request::new(Body::from(
@@ -1229,4 +1680,332 @@ mod tests {
         let expected = transaction.transaction_id();
         assert_eq!(expected, actual);
     }
+
+    #[tokio::test]
+    async fn test_send_transaction_rejected() {
+        // Initialize a new rpc, with a handler for Ledger requests that rejects the transaction.
+        let rpc = new_rpc_impl_with_ledger_handler::<Testnet2, Client<Testnet2>, RocksDB>(|transaction| {
+            Err(TransactionError::AlreadyInLedger(transaction.transaction_id().to_string()))
+        });
+
+        // Initialize a new request that calls the `sendtransaction` endpoint.
+        let request = Request::new(Body::from(
+            r#"{
+	"jsonrpc": "2.0",
+	"id": "1",
+	"method": "sendtransaction",
+	"params": [
+        "a8a2358b0aa49123434b1757172e034d119cc282cfceb42210f886920ae6754d043539a82da297d5578504fcab003300ccecab3ae59f05892805b378bcdbacfa4fd24818e4048c8a09e98e034cb6790c0100ccb7bfe9e0933daa1b446a4cab28717459db173754d9535ba0bffc5b8c304c0981407a85e3220300896eab8c06ec5fa91701c553c0d7734b9d357fb8a6dea1006e9862d7eed6f07666f41c734f4f8ed80031b45cb12bf54be8d3c025ffe51f120674438b929cfcefb97470058145ebecc711c101102c4d8d388cbbd15c2759072b70ebde4aa0ae864ed39302e39d56a12a7e58416204545e1300741965120a0e985d7c48ad6b925f8f88f9c0bd33a8563e780a0786e1f898bfe65addb2e3f80b5c10ece2389438fcea239bd737e412e29227cdefdc74a37d80132a03b0f5060c124d0b46b99747a7aeeceff1589c08c6eae67cd2de4eb3810e245e654b5f1e05126ec4432ff09c76776f1a8169f1e72959f1eedd0cc48d4349587ab0025aa208fed92eda33f6e4f06048df167ddabae4e651cfc1cd7339b02cf5d11ef76df009d8770d20502290ee7fc3aa005882992419dafa16f9002b0ec872296212cdb20f2a6f2593346c2129188b14446ab2e45522f1b977e38a305bfbaa4199097cc103f90756979113bcde9aeb84d9930cfe1d67f7116f41b878ba9b501ceb7daa400a9de48e187395dc67a674b02bd6a455f56eb08bee168f96579be85d090844c004d697e8ec02e0ca759ec904a8a3991c015b65523ee24a4aea89b2eda0da773d11aac359834fe3647ee20c08b10bccd39b638aed2d24d6fb2268739e390ed9850b9db40835eb280e4a43c8fd444c408fa9f038f5a23ac42b30846269d507fdd90e0309e3144dd23baef11d1c3a806cc81de2ce55dd31e8595d753be30f84a0a509d005e9b057d2fa2ddf559b4ad8f50cd86fc3ebb928c0ab8a6eb96806171e85026a619d98561decb838a85760b971cad2ce7496796454a0b8b11d80f7f2139a03cd5d670d229fe3cdee33abfde96bd4e60fddfed184a88b446a3dd20b5b0dbb0a644aac3ca4c3590b18b63ae0190f83316faba89db05b7bf339bf14aa478f410572e86a32c8f7b22c7e479cbbea00934243d2b45339d2ffbd68db439e23d04c0e93b694efe478ebf8816fa259c8f85f873eb4f51ed7a037c6577a2f5f7907e3082efbffffffffffff95ff564b8f87c7541b871536baa809a1f5902f37c23a98a00f73bd4d48c567a12eda954376db7cb4d6889957224e0946985bc13106c2b2359523786e1d443a33b4362ef3e51ec2f550ef15d428809c9a3985da1a8dd8a95d1b808538be0a2200f744755a098ecf7106edfb3795396c6e477eeb5b4f435299edfa549502c91b79c83b623039731d23b41878e74fd5680b186f6601898fe109cfe6e2816252a2684a225d3d7c93060bd32b91ddb4c3e3512c87c61d4fa624fc8cddaa0a353f1181c096a058d420b23c4b435c1857356e4c8eed7605df993bac56b9a860cdf9b7e6492116e77c380d7c1d15c853a4298ffa84facfc18010eac98db7391376d86214d9a1474c89c3d259c1d69da8e455718d433f5cbf62de324705771b55358fbf00010000"
+    ]
+}"#,
+        ));
+
+        // Send the request to the rpc.
+        let response = handle_rpc(caller(), rpc, request)
+            .await
+            .expect("Test rpc failed to process request");
+
+        // Check the rejection reason is returned.
+        let response_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let response_json: jrt::Response<serde_json::Value, String> = serde_json::from_slice(&response_bytes).unwrap();
+        let error = response_json.payload.expect_err("Expected the transaction to be rejected");
+        assert_eq!(error.code, jrt::ErrorCode::ServerError(-32011));
+    }
+
+    #[tokio::test]
+    async fn test_get_block_template() {
+        // Initialize a new rpc.
+        let rpc = new_rpc_impl::<Testnet2, Client<Testnet2>, RocksDB>();
+
+        // Initialize a new request that calls the `getblocktemplate` endpoint.
+        let request = Request::new(Body::from(
+            r#"{
+	"jsonrpc": "2.0",
+	"id": "1",
+	"method": "getblocktemplate",
+	"params": []
+}"#,
+        ));
+
+        // Send the request to the rpc.
+        let response = handle_rpc(caller(), rpc, request)
+            .await
+            .expect("Test rpc failed to process request");
+
+        // Process the response into a block template.
+        let actual: serde_json::Value = process_response(response).await;
+
+        // Check the block template extends the genesis block.
+        let genesis = Testnet2::genesis_block();
+        assert_eq!(actual["previous_block_hash"], serde_json::json!(genesis.hash()));
+        assert_eq!(actual["block_height"], 1);
+        assert_eq!(actual["coinbase_reward"], Block::<Testnet2>::block_reward(1).0);
+        assert_eq!(actual["transactions"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_submit_block() {
+        let terminator = AtomicBool::new(false);
+
+        // Initialize a new rpc, with a handler for Ledger requests.
+        let credentials = RpcCredentials {
+            username: "root".to_string(),
+            password: "pass".to_string(),
+        };
+        let peers = new_peers::<Testnet2, Client<Testnet2>>();
+        let ledger = new_ledger_state::<Testnet2, RocksDB>();
+        let network_ledger = Arc::new(RwLock::new(new_ledger::<Testnet2, Client<Testnet2>, RocksDB>()));
+        let (peers_router, _peers_handler) = mpsc::channel(1024);
+        let (ledger_router, mut ledger_handler) = mpsc::channel(1024);
+        let rpc = RpcImpl::<Testnet2, Client<Testnet2>>::new(credentials, peers, peers_router, ledger, network_ledger, ledger_router);
+
+        // Mine the next block on a separate ledger.
+        let address = Account::<Testnet2>::new(&mut thread_rng()).address();
+        let block = new_ledger_state::<Testnet2, RocksDB>()
+            .mine_next_block(address, &[], &terminator, &mut thread_rng())
+            .expect("Failed to mine");
+
+        // Check the block is routed to the ledger, accepting it the first time and rejecting it afterwards.
+        let expected_block = block.clone();
+        tokio::spawn(async move {
+            let mut is_first_request = true;
+            while let Some(request) = ledger_handler.recv().await {
+                match request {
+                    LedgerRequest::SubmitBlock(_, routed_block, responder) => {
+                        assert_eq!(expected_block, routed_block);
+                        let result = match is_first_request {
+                            true => Ok(routed_block.hash()),
+                            false => Err(anyhow!("Block {} was not added to the canon chain", routed_block.height())),
+                        };
+                        is_first_request = false;
+                        let _ = responder.send(result);
+                    }
+                    _ => panic!("Expected a `SubmitBlock` request"),
+                }
+            }
+        });
+
+        // Submit the block.
+        let block_hex = hex::encode(block.to_bytes_le().unwrap());
+        let actual = rpc.submit_block(block_hex.clone()).await.unwrap();
+        assert_eq!(block.hash(), actual);
+
+        // Check a block rejected by the ledger is reported.
+        assert!(rpc.submit_block(block_hex).await.is_err());
+
+        // Check a block that does not extend the canonical chain is rejected.
+        let genesis_hex = hex::encode(Testnet2::genesis_block().to_bytes_le().unwrap());
+        assert!(rpc.submit_block(genesis_hex).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_validate_raw_transaction() {
+        // Initialize a new rpc.
+        let rpc = new_rpc_impl::<Testnet2, Client<Testnet2>, RocksDB>();
+
+        // Retrieve a transaction that already exists in the ledger.
+        let transaction = Testnet2::genesis_block().transactions().iter().next().unwrap().clone();
+        let transaction_hex = hex::encode(transaction.to_bytes_le().unwrap());
+
+        // Validate the transaction.
+        let actual = rpc.validate_raw_transaction(transaction_hex).await.unwrap();
+
+        // Check the transaction is reported as already in the ledger.
+        assert_eq!(actual["transaction_id"], serde_json::json!(transaction.transaction_id()));
+        assert_eq!(actual["is_valid"], false);
+        let codes: Vec<i64> = actual["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|error| error["code"].as_i64().unwrap())
+            .collect();
+        assert!(codes.contains(&-32011));
+    }
+
+    #[tokio::test]
+    async fn test_ban_peer() {
+        // Initialize a new rpc, with a handler for Peers requests.
+        let credentials = RpcCredentials {
+            username: "root".to_string(),
+            password: "pass".to_string(),
+        };
+        let peers = new_peers::<Testnet2, Client<Testnet2>>();
+        let ledger = new_ledger_state::<Testnet2, RocksDB>();
+        let network_ledger = Arc::new(RwLock::new(new_ledger::<Testnet2, Client<Testnet2>, RocksDB>()));
+        let (peers_router, mut peers_handler) = mpsc::channel(1024);
+        let (ledger_router, _ledger_handler) = mpsc::channel(1024);
+        let rpc = RpcImpl::<Testnet2, Client<Testnet2>>::new(credentials, peers, peers_router, ledger, network_ledger, ledger_router);
+
+        // Initialize a new request that calls the `banpeer` endpoint.
+        let request = new_authorized_request(
+            r#"{
+	"jsonrpc": "2.0",
+	"id": "1",
+	"method": "banpeer",
+	"params": ["127.0.0.1:4132", 60]
+}"#,
+        );
+
+        // Send the request to the rpc.
+        let response = handle_rpc(caller(), rpc, request)
+            .await
+            .expect("Test rpc failed to process request");
+
+        // Process the response into a timestamp.
+        let actual: i64 = process_response(response).await;
+
+        // Check the ban was routed to the peers.
+        match peers_handler.recv().await {
+            Some(PeersRequest::PeerBanned(peer_ip, banned_until)) => {
+                assert_eq!(peer_ip, "127.0.0.1:4132".parse().unwrap());
+                assert_eq!(banned_until, actual);
+            }
+            _ => panic!("Expected a `PeerBanned` request"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_banned() {
+        // Initialize a new rpc.
+        let rpc = new_rpc_impl::<Testnet2, Client<Testnet2>, RocksDB>();
+
+        // Initialize a new request that calls the `listbanned` endpoint.
+        let request = new_authorized_request(
+            r#"{
+	"jsonrpc": "2.0",
+	"id": "1",
+	"method": "listbanned",
+	"params": []
+}"#,
+        );
+
+        // Send the request to the rpc.
+        let response = handle_rpc(caller(), rpc, request)
+            .await
+            .expect("Test rpc failed to process request");
+
+        // Process the response into a list of banned peers.
+        let actual: Vec<serde_json::Value> = process_response(response).await;
+
+        // Check the list of banned peers.
+        assert!(actual.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_miner_control() {
+        // Initialize a new mining rpc, with a handler for Ledger requests.
+        let credentials = RpcCredentials {
+            username: "root".to_string(),
+            password: "pass".to_string(),
+        };
+        let peers = new_peers::<Testnet2, Miner<Testnet2>>();
+        let ledger = new_ledger_state::<Testnet2, RocksDB>();
+        let network_ledger = Arc::new(RwLock::new(new_ledger::<Testnet2, Miner<Testnet2>, RocksDB>()));
+        let (peers_router, _peers_handler) = mpsc::channel(1024);
+        let (ledger_router, mut ledger_handler) = mpsc::channel(1024);
+        let rpc = RpcImpl::<Testnet2, Miner<Testnet2>>::new(credentials, peers, peers_router, ledger, network_ledger, ledger_router);
+
+        // Initialize a new request that calls the `setminingthreads` endpoint.
+        let request = new_authorized_request(
+            r#"{
+	"jsonrpc": "2.0",
+	"id": "1",
+	"method": "setminingthreads",
+	"params": [2]
+}"#,
+        );
+
+        // Send the request to the rpc.
+        let response = handle_rpc(caller(), rpc.clone(), request)
+            .await
+            .expect("Test rpc failed to process request");
+        let actual: bool = process_response(response).await;
+        assert!(actual);
+
+        // Check the request was routed to the ledger.
+        match ledger_handler.recv().await {
+            Some(LedgerRequest::SetMiningThreads(number_of_threads)) => assert_eq!(number_of_threads, 2),
+            _ => panic!("Expected a `SetMiningThreads` request"),
+        }
+
+        // Check invalid miner requests are rejected.
+        assert!(rpc.set_mining_threads(0).await.is_err());
+        assert!(rpc.set_mining_recipient("not an address".to_string()).await.is_err());
+
+        // Check miner requests are rejected on a client node.
+        let rpc = new_rpc_impl::<Testnet2, Client<Testnet2>, RocksDB>();
+        assert!(rpc.stop_mining().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_request() {
+        // Initialize a new rpc.
+        let rpc = new_rpc_impl::<Testnet2, Client<Testnet2>, RocksDB>();
+
+        // Initialize a new request that calls the `listbanned` endpoint, with invalid credentials.
+        let request = Request::builder()
+            .header(hyper::header::AUTHORIZATION, format!("Basic {}", base64::encode("root:wrong")))
+            .body(Body::from(
+                r#"{
+	"jsonrpc": "2.0",
+	"id": "1",
+	"method": "listbanned",
+	"params": []
+}"#,
+            ))
+            .unwrap();
+
+        // Send the request to the rpc.
+        let response = handle_rpc(caller(), rpc, request)
+            .await
+            .expect("Test rpc failed to process request");
+
+        // Check the request was rejected.
+        assert_eq!(response.status(), hyper::StatusCode::UNAUTHORIZED);
+        assert!(response.headers().contains_key(hyper::header::WWW_AUTHENTICATE));
+    }
+
+    #[test]
+    fn test_rpc_credentials() {
+        let credentials = RpcCredentials {
+            username: "root".to_string(),
+            password: "pass".to_string(),
+        };
+
+        // Check the credentials are authorized.
+        let auth = format!("Basic {}", base64::encode("root:pass"));
+        assert!(credentials.is_authorized(Some(&auth)));
+
+        // Check invalid credentials are not authorized.
+        let auth = format!("Basic {}", base64::encode("root:pas"));
+        assert!(!credentials.is_authorized(Some(&auth)));
+        assert!(!credentials.is_authorized(Some("Basic not-base64")));
+        assert!(!credentials.is_authorized(Some("Bearer cm9vdDpwYXNz")));
+        assert!(!credentials.is_authorized(None));
+    }
+
+    #[test]
+    fn test_rpc_credentials_from_cookie_file() {
+        let path = temp_dir().join(".cookie");
+        let credentials = RpcCredentials::from_cookie_file(&path, "root".to_string()).unwrap();
+
+        // Check the cookie file holds the generated credentials.
+        let cookie = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(cookie, format!("root:{}", credentials.password));
+        assert_ne!(credentials.password, "pass");
+
+        // Check the cookie file credentials are authorized.
+        let auth = format!("Basic {}", base64::encode(&cookie));
+        assert!(credentials.is_authorized(Some(&auth)));
+
+        // Check the cookie file is only readable by the owner, including when it is regenerated.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+            RpcCredentials::from_cookie_file(&path, "root".to_string()).unwrap();
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
 }
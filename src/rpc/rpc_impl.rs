@@ -14,27 +14,41 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
-//! Implementation of public RPC endpoints.
+//! Implementation of public and private RPC endpoints.
 //!
 //! See [RpcFunctions](../trait.RpcFunctions.html) for documentation of public endpoints.
+//! See [ProtectedRpcFunctions](../trait.ProtectedRpcFunctions.html) for documentation of private endpoints.
 
 use crate::{
-    rpc::{rpc::*, rpc_trait::RpcFunctions},
+    ledger::Ledger,
+    rpc::{
+        rpc::*,
+        rpc_trait::{ProtectedRpcFunctions, RpcFunctions},
+    },
     Environment,
     LedgerRequest,
     LedgerRouter,
+    Message,
+    NodeType,
     Peers,
+    PeersRequest,
+    PeersRouter,
+    TransactionError,
 };
 use snarkos_ledger::{LedgerState, Metadata};
 use snarkvm::{
-    dpc::{Block, BlockHeader, Network, RecordCiphertext, Transaction, Transactions, Transition},
+    dpc::{Address, Block, BlockHeader, Network, RecordCiphertext, Transaction, Transactions, Transition},
     utilities::FromBytes,
 };
 
+use chrono::Utc;
 use jsonrpc_core::Value;
 use snarkvm::utilities::ToBytes;
-use std::{cmp::max, net::SocketAddr, ops::Deref, sync::Arc};
-use tokio::sync::RwLock;
+use std::{cmp::max, net::SocketAddr, ops::Deref, str::FromStr, sync::Arc, time::Instant};
+use tokio::sync::{oneshot, RwLock};
+
+/// The maximum number of mined blocks returned by `getminingstats`.
+const MAX_MINED_BLOCKS: usize = 100;
 
 #[derive(Debug, Error)]
 pub enum RpcError {
@@ -52,6 +66,8 @@ pub enum RpcError {
     SerdeJson(#[from] serde_json::Error),
     #[error("{}", _0)]
     StdIOError(#[from] std::io::Error),
+    #[error("{}", _0)]
+    Transaction(#[from] TransactionError),
 }
 
 impl From<RpcError> for std::io::Error {
@@ -63,11 +79,14 @@ impl From<RpcError> for std::io::Error {
 #[doc(hidden)]
 pub struct RpcInner<N: Network, E: Environment> {
     peers: Arc<RwLock<Peers<N, E>>>,
+    peers_router: PeersRouter<N, E>,
     ledger: LedgerState<N>,
+    network_ledger: Arc<RwLock<Ledger<N, E>>>,
     ledger_router: LedgerRouter<N, E>,
     /// RPC credentials for accessing guarded endpoints
-    #[allow(unused)]
     pub(crate) credentials: RpcCredentials,
+    /// The time at which the RPC server was launched
+    launched: Instant,
 }
 
 /// Implements RPC HTTP endpoint functions for a node.
@@ -87,16 +106,33 @@ impl<N: Network, E: Environment> RpcImpl<N, E> {
     pub fn new(
         credentials: RpcCredentials,
         peers: Arc<RwLock<Peers<N, E>>>,
+        peers_router: PeersRouter<N, E>,
         ledger: LedgerState<N>,
+        network_ledger: Arc<RwLock<Ledger<N, E>>>,
         ledger_router: LedgerRouter<N, E>,
     ) -> Self {
         Self(Arc::new(RpcInner {
             peers,
+            peers_router,
             ledger,
+            network_ledger,
             ledger_router,
             credentials,
+            launched: Instant::now(),
         }))
     }
+
+    /// Routes the given miner request to the ledger, returning `true` if the request was sent.
+    async fn route_miner_request(&self, request: LedgerRequest<N, E>) -> Result<bool, RpcError> {
+        // Ensure this node is a mining node.
+        if E::NODE_TYPE != NodeType::Miner {
+            return Err(RpcError::Message("This node is not a mining node".to_string()));
+        }
+        if let Err(error) = self.ledger_router.send(request).await {
+            return Err(RpcError::Message(format!("Failed to update the miner: {}", error)));
+        }
+        Ok(true)
+    }
 }
 
 #[async_trait::async_trait]
@@ -136,9 +172,10 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcImpl<N, E> {
         Ok(self.ledger.get_block(block_height)?)
     }
 
-    /// Returns up to `MAXIMUM_BLOCK_REQUEST` blocks from the given `start_block_height` to `end_block_height` (inclusive).
+    /// Returns up to `maximum_block_request` blocks from the given `start_block_height` to `end_block_height` (inclusive).
     async fn get_blocks(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<Block<N>>, RpcError> {
-        let safe_start_height = max(start_block_height, end_block_height.saturating_sub(E::MAXIMUM_BLOCK_REQUEST - 1));
+        let maximum_block_request = self.network_ledger.read().await.maximum_block_request();
+        let safe_start_height = max(start_block_height, end_block_height.saturating_sub(maximum_block_request - 1));
         Ok(self.ledger.get_blocks(safe_start_height, end_block_height)?)
     }
 
@@ -153,9 +190,10 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcImpl<N, E> {
         Ok(self.ledger.get_block_hash(block_height)?)
     }
 
-    /// Returns up to `MAXIMUM_BLOCK_REQUEST` block hashes from the given `start_block_height` to `end_block_height` (inclusive).
+    /// Returns up to `maximum_block_request` block hashes from the given `start_block_height` to `end_block_height` (inclusive).
     async fn get_block_hashes(&self, start_block_height: u32, end_block_height: u32) -> Result<Vec<N::BlockHash>, RpcError> {
-        let safe_start_height = max(start_block_height, end_block_height.saturating_sub(E::MAXIMUM_BLOCK_REQUEST - 1));
+        let maximum_block_request = self.network_ledger.read().await.maximum_block_request();
+        let safe_start_height = max(start_block_height, end_block_height.saturating_sub(maximum_block_request - 1));
         Ok(self.ledger.get_block_hashes(safe_start_height, end_block_height)?)
     }
 
@@ -196,60 +234,269 @@ impl<N: Network, E: Environment> RpcFunctions<N> for RpcImpl<N, E> {
         Ok(self.ledger.get_transition(&transition_id)?)
     }
 
+    /// Returns the IP addresses of all connected peers.
     async fn get_connected_peers(&self) -> Result<Vec<SocketAddr>, RpcError> {
         Ok(self.peers.read().await.connected_peers())
     }
 
+    /// Returns information about the node.
+    async fn get_node_info(&self) -> Result<Value, RpcError> {
+        let number_of_connected_peers = self.peers.read().await.num_connected_peers();
+        let ledger = self.network_ledger.read().await;
+        Ok(serde_json::json!({
+            "node_type": E::NODE_TYPE,
+            "status": ledger.status(),
+            "version": env!("CARGO_PKG_VERSION"),
+            "message_version": E::MESSAGE_VERSION,
+            "network": N::NETWORK_NAME,
+            "latest_block_height": ledger.latest_block_height(),
+            "number_of_connected_peers": number_of_connected_peers,
+            "uptime_in_secs": self.launched.elapsed().as_secs(),
+        }))
+    }
+
+    /// Returns the sync progress of the node with its connected peers.
+    async fn get_sync_status(&self) -> Result<Value, RpcError> {
+        let ledger = self.network_ledger.read().await;
+        let peers: Vec<Value> = ledger
+            .peers_sync_state()
+            .into_iter()
+            .map(|(peer_ip, state, number_of_block_requests)| {
+                serde_json::json!({
+                    "ip": peer_ip,
+                    "latest_block_height": state.map(|(_, block_height)| block_height),
+                    "is_fork": state.and_then(|(is_fork, _)| is_fork),
+                    "block_requests": number_of_block_requests,
+                })
+            })
+            .collect();
+        Ok(serde_json::json!({
+            "status": ledger.status(),
+            "is_syncing": ledger.is_syncing(),
+            "latest_block_height": ledger.latest_block_height(),
+            "latest_peer_block_height": ledger.latest_peer_block_height(),
+            "block_requests": ledger.number_of_block_requests(),
+            "peers": peers,
+        }))
+    }
+
+    /// Returns the transaction IDs of the unconfirmed transactions in the memory pool.
+    async fn get_memory_pool(&self) -> Result<Vec<N::TransactionID>, RpcError> {
+        let transactions = self.network_ledger.read().await.memory_pool_transactions();
+        Ok(transactions.iter().map(|transaction| transaction.transaction_id()).collect())
+    }
+
+    /// Returns the number of unconfirmed transactions in the memory pool, and their total size in bytes.
+    async fn get_memory_pool_info(&self) -> Result<Value, RpcError> {
+        let transactions = self.network_ledger.read().await.memory_pool_transactions();
+        let mut size_in_bytes = 0;
+        for transaction in transactions.iter() {
+            size_in_bytes += transaction.to_bytes_le()?.len();
+        }
+        Ok(serde_json::json!({ "size": transactions.len(), "bytes": size_in_bytes }))
+    }
+
+    /// Returns the unconfirmed transaction given the transaction ID, if it exists in the memory pool.
+    async fn get_unconfirmed_transaction(&self, transaction_id: serde_json::Value) -> Result<Transaction<N>, RpcError> {
+        let transaction_id: N::TransactionID = serde_json::from_value(transaction_id)?;
+        match self.network_ledger.read().await.get_unconfirmed_transaction(&transaction_id) {
+            Some(transaction) => Ok(transaction),
+            None => Err(RpcError::Message(format!(
+                "Transaction {} is not in the memory pool",
+                transaction_id
+            ))),
+        }
+    }
+
     /// Returns the transaction ID. If the given transaction is valid, it is added to the memory pool and propagated to all peers.
     async fn send_transaction(&self, transaction_hex: String) -> Result<N::TransactionID, RpcError> {
         let transaction: Transaction<N> = FromBytes::from_bytes_le(&hex::decode(transaction_hex)?)?;
-        // Route an `UnconfirmedTransaction` to the ledger.
-        let request = LedgerRequest::UnconfirmedTransaction("0.0.0.0:3032".parse().unwrap(), transaction.clone());
+        let local_ip = self.peers.read().await.local_ip();
+
+        // Route a `SendTransaction` to the ledger, and wait for the result.
+        let (responder, receiver) = oneshot::channel();
+        let request = LedgerRequest::SendTransaction(local_ip, transaction, responder);
         if let Err(error) = self.ledger_router.send(request).await {
-            warn!("[UnconfirmedTransaction] {}", error);
-        }
-        Ok(transaction.transaction_id())
-    }
-
-    // /// Validate and return if the transaction is valid.
-    // async fn validate_raw_transaction(&self, transaction_bytes: String) -> Result<bool, RpcError> {
-    //     let transaction_bytes = hex::decode(transaction_bytes)?;
-    //     let transaction = Testnet1Transaction::read_le(&transaction_bytes[..])?;
-    //
-    //     Ok(self
-    //         .sync_handler()?
-    //         .consensus
-    //         .verify_transactions(vec![transaction.serialize()?])
-    //         .await)
-    // }
-    //
-    // /// Returns the current mempool and sync information known by this node.
-    // async fn get_block_template(&self) -> Result<BlockTemplate, RpcError> {
-    //     let canon = self.storage.canon().await?;
-    //
-    //     let block = self.storage.get_block_header(&canon.hash).await?;
-    //
-    //     let time = Utc::now().timestamp();
-    //
-    //     let full_transactions = self.node.expect_sync().consensus.fetch_memory_pool().await;
-    //
-    //     let transaction_strings = full_transactions
-    //         .iter()
-    //         .map(|x| Ok(hex::encode(to_bytes_le![x]?)))
-    //         .collect::<Result<Vec<_>, RpcError>>()?;
-    //
-    //     let mut coinbase_value = get_block_reward(canon.block_height as u32 + 1);
-    //     for transaction in full_transactions.iter() {
-    //         coinbase_value = coinbase_value.add(transaction.value_balance)
-    //     }
-    //
-    //     Ok(BlockTemplate {
-    //         previous_block_hash: hex::encode(&block.hash().0),
-    //         block_height: canon.block_height as u32 + 1,
-    //         time,
-    //         difficulty_target: self.consensus_parameters()?.get_block_difficulty(&block, time),
-    //         transactions: transaction_strings,
-    //         coinbase_value: coinbase_value.0 as u64,
-    //     })
-    // }
+            return Err(RpcError::Message(format!("Failed to route the transaction: {}", error)));
+        }
+        match receiver.await {
+            Ok(result) => Ok(result?),
+            Err(_) => Err(RpcError::Message("The ledger did not process the transaction".to_string())),
+        }
+    }
+
+    /// Returns every failed check of the given transaction against the ledger and memory pool, without broadcasting it.
+    async fn validate_raw_transaction(&self, transaction_hex: String) -> Result<Value, RpcError> {
+        let transaction: Transaction<N> = FromBytes::from_bytes_le(&hex::decode(transaction_hex)?)?;
+        let errors = self.network_ledger.read().await.validate_transaction(&transaction);
+
+        let errors: Vec<Value> = errors
+            .iter()
+            .map(|error| {
+                let (code, _) = transaction_error_code(error);
+                serde_json::json!({ "code": code, "message": error.to_string() })
+            })
+            .collect();
+
+        Ok(serde_json::json!({
+            "transaction_id": transaction.transaction_id(),
+            "is_valid": errors.is_empty(),
+            "errors": errors,
+        }))
+    }
+
+    /// Returns the state of the miner, a summary of the blocks mined by this node, and the most recently mined blocks.
+    async fn get_mining_stats(&self) -> Result<Value, RpcError> {
+        let ledger = self.network_ledger.read().await;
+        let mining_history = ledger.mining_history();
+        let blocks: Vec<_> = mining_history.mined_blocks().into_iter().take(MAX_MINED_BLOCKS).collect();
+
+        Ok(serde_json::json!({
+            "is_mining": ledger.is_mining(),
+            "is_mining_enabled": ledger.is_mining_enabled(),
+            "mining_recipient": ledger.mining_recipient(),
+            "mining_threads": ledger.mining_threads(),
+            "stats": mining_history.stats(),
+            "blocks": blocks,
+        }))
+    }
+
+    /// Returns the parameters and memory pool transactions for mining the next block.
+    async fn get_block_template(&self) -> Result<Value, RpcError> {
+        let (template, transactions) = {
+            let ledger = self.network_ledger.read().await;
+            (ledger.get_block_template(), ledger.memory_pool_transactions())
+        };
+
+        let transactions = transactions
+            .iter()
+            .map(|transaction| Ok(hex::encode(transaction.to_bytes_le()?)))
+            .collect::<Result<Vec<_>, RpcError>>()?;
+
+        Ok(serde_json::json!({
+            "previous_block_hash": template.previous_block_hash(),
+            "block_height": template.block_height(),
+            "block_timestamp": template.block_timestamp(),
+            "difficulty_target": template.difficulty_target(),
+            "ledger_root": template.ledger_root(),
+            "coinbase_reward": template.block_reward().0,
+            "transactions": transactions,
+        }))
+    }
+
+    /// Returns the block hash, once the given block is added to the canonical chain and propagated to all peers.
+    async fn submit_block(&self, block_hex: String) -> Result<N::BlockHash, RpcError> {
+        let block: Block<N> = FromBytes::from_bytes_le(&hex::decode(block_hex)?)?;
+
+        // Ensure the block is well-formed.
+        if !block.is_valid() {
+            return Err(RpcError::Message(format!("Block {} is invalid", block.height())));
+        }
+        // Ensure the block extends the canonical chain.
+        let latest_block_hash = self.network_ledger.read().await.latest_block_hash();
+        if block.previous_block_hash() != latest_block_hash {
+            return Err(RpcError::Message(format!(
+                "Block {} does not extend the latest block {}",
+                block.height(),
+                latest_block_hash
+            )));
+        }
+
+        // Route a `SubmitBlock` to the ledger, and wait for the result.
+        let local_ip = self.peers.read().await.local_ip();
+        let (responder, receiver) = oneshot::channel();
+        let request = LedgerRequest::SubmitBlock(local_ip, block, responder);
+        if let Err(error) = self.ledger_router.send(request).await {
+            return Err(RpcError::Message(format!("Failed to route the block: {}", error)));
+        }
+        match receiver.await {
+            Ok(result) => Ok(result?),
+            Err(_) => Err(RpcError::Message("The ledger did not process the block".to_string())),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<N: Network, E: Environment> ProtectedRpcFunctions for RpcImpl<N, E> {
+    /// Attempts to connect to the given peers, returning `true` if the connection requests were sent.
+    async fn connect(&self, peer_ips: Vec<SocketAddr>) -> Result<bool, RpcError> {
+        for peer_ip in peer_ips {
+            // Route a `Connect` request to the peers.
+            let request = PeersRequest::Connect(peer_ip, self.ledger_router.clone());
+            if let Err(error) = self.peers_router.send(request).await {
+                return Err(RpcError::Message(format!("Failed to connect to {}: {}", peer_ip, error)));
+            }
+        }
+        Ok(true)
+    }
+
+    /// Disconnects from the given peer, returning `true` if the disconnect request was sent.
+    async fn disconnect(&self, peer_ip: SocketAddr) -> Result<bool, RpcError> {
+        // Ensure the node is connected to the peer.
+        if !self.peers.read().await.is_connected_to(peer_ip) {
+            return Err(RpcError::Message(format!("Not connected to {}", peer_ip)));
+        }
+        // Send a `Disconnect` message to the peer.
+        let request = PeersRequest::MessageSend(peer_ip, Message::Disconnect);
+        if let Err(error) = self.peers_router.send(request).await {
+            return Err(RpcError::Message(format!("Failed to disconnect from {}: {}", peer_ip, error)));
+        }
+        Ok(true)
+    }
+
+    /// Bans the given peer for the given duration (or `BAN_DURATION_IN_SECS` by default), returning the timestamp until which it is banned.
+    async fn ban_peer(&self, peer_ip: SocketAddr, duration_in_secs: Option<u64>) -> Result<i64, RpcError> {
+        let duration_in_secs = duration_in_secs.unwrap_or(E::BAN_DURATION_IN_SECS);
+        let banned_until = Utc::now().timestamp().saturating_add(duration_in_secs as i64);
+        // Route a `PeerBanned` request to the peers.
+        if let Err(error) = self.peers_router.send(PeersRequest::PeerBanned(peer_ip, banned_until)).await {
+            return Err(RpcError::Message(format!("Failed to ban {}: {}", peer_ip, error)));
+        }
+        Ok(banned_until)
+    }
+
+    /// Lifts the ban on the given peer, returning `true` if the unban request was sent.
+    async fn unban_peer(&self, peer_ip: SocketAddr) -> Result<bool, RpcError> {
+        // Route a `PeerUnbanned` request to the peers.
+        if let Err(error) = self.peers_router.send(PeersRequest::PeerUnbanned(peer_ip)).await {
+            return Err(RpcError::Message(format!("Failed to unban {}: {}", peer_ip, error)));
+        }
+        Ok(true)
+    }
+
+    /// Returns the list of banned peers, with the timestamp until which each is banned.
+    async fn list_banned(&self) -> Result<Vec<Value>, RpcError> {
+        let banned_peers = self.peers.read().await.banned_peers();
+        Ok(banned_peers
+            .into_iter()
+            .map(|(peer_ip, banned_until)| serde_json::json!({ "ip": peer_ip, "banned_until": banned_until }))
+            .collect())
+    }
+
+    /// Resumes the miner, returning `true` if the request was sent.
+    async fn start_mining(&self) -> Result<bool, RpcError> {
+        self.route_miner_request(LedgerRequest::StartMining).await
+    }
+
+    /// Pauses the miner, returning `true` if the request was sent.
+    async fn stop_mining(&self) -> Result<bool, RpcError> {
+        self.route_miner_request(LedgerRequest::StopMining).await
+    }
+
+    /// Sets the recipient of the coinbase rewards, returning `true` if the request was sent.
+    async fn set_mining_recipient(&self, recipient: String) -> Result<bool, RpcError> {
+        let recipient = match Address::<N>::from_str(&recipient) {
+            Ok(recipient) => recipient,
+            Err(error) => return Err(RpcError::Message(format!("Invalid address {}: {}", recipient, error))),
+        };
+        self.route_miner_request(LedgerRequest::SetMiningRecipient(recipient)).await
+    }
+
+    /// Sets the number of threads used by the miner, returning `true` if the request was sent.
+    async fn set_mining_threads(&self, number_of_threads: usize) -> Result<bool, RpcError> {
+        if number_of_threads == 0 {
+            return Err(RpcError::Message("The number of mining threads must be at least 1".to_string()));
+        }
+        self.route_miner_request(LedgerRequest::SetMiningThreads(number_of_threads)).await
+    }
 }
@@ -17,7 +17,7 @@
 //! Definition of the public and private RPC endpoints.
 
 use crate::rpc::rpc_impl::RpcError;
-use snarkvm::dpc::{Block, BlockHeader, Network, RecordCiphertext, Transactions, Transition};
+use snarkvm::dpc::{Block, BlockHeader, Network, RecordCiphertext, Transaction, Transactions, Transition};
 
 use std::net::SocketAddr;
 
@@ -77,20 +77,75 @@ pub trait RpcFunctions<N: Network> {
     #[doc = include_str!("./documentation/public_endpoints/gettransition.md")]
     async fn get_transition(&self, transition_id: serde_json::Value) -> Result<Transition<N>, RpcError>;
 
-    #[doc = include_str!("./documentation/public_endpoints/gettransition.md")]
+    #[doc = include_str!("./documentation/public_endpoints/getconnectedpeers.md")]
     async fn get_connected_peers(&self) -> Result<Vec<SocketAddr>, RpcError>;
 
+    #[doc = include_str!("./documentation/public_endpoints/getnodeinfo.md")]
+    async fn get_node_info(&self) -> Result<serde_json::Value, RpcError>;
+
+    #[doc = include_str!("./documentation/public_endpoints/getsyncstatus.md")]
+    async fn get_sync_status(&self) -> Result<serde_json::Value, RpcError>;
+
+    #[doc = include_str!("./documentation/public_endpoints/getmemorypool.md")]
+    async fn get_memory_pool(&self) -> Result<Vec<N::TransactionID>, RpcError>;
+
+    #[doc = include_str!("./documentation/public_endpoints/getmemorypoolinfo.md")]
+    async fn get_memory_pool_info(&self) -> Result<serde_json::Value, RpcError>;
+
+    #[doc = include_str!("./documentation/public_endpoints/getunconfirmedtransaction.md")]
+    async fn get_unconfirmed_transaction(&self, transaction_id: serde_json::Value) -> Result<Transaction<N>, RpcError>;
+
+    #[doc = include_str!("./documentation/public_endpoints/getminingstats.md")]
+    async fn get_mining_stats(&self) -> Result<serde_json::Value, RpcError>;
+
+    #[doc = include_str!("./documentation/public_endpoints/getblocktemplate.md")]
+    async fn get_block_template(&self) -> Result<serde_json::Value, RpcError>;
+
+    #[doc = include_str!("./documentation/public_endpoints/submitblock.md")]
+    async fn submit_block(&self, block_hex: String) -> Result<N::BlockHash, RpcError>;
+
     #[doc = include_str!("./documentation/public_endpoints/sendtransaction.md")]
     async fn send_transaction(&self, transaction_bytes: String) -> Result<N::TransactionID, RpcError>;
 
-    // #[doc = include_str!("../documentation/public_endpoints/validaterawtransaction.md")]
-    // async fn validate_raw_transaction(&self, transaction_bytes: String) -> Result<bool, RpcError>;
-    //
+    #[doc = include_str!("./documentation/public_endpoints/validaterawtransaction.md")]
+    async fn validate_raw_transaction(&self, transaction_bytes: String) -> Result<serde_json::Value, RpcError>;
+
     // #[doc = include_str!("../documentation/public_endpoints/getconnectioncount.md")]
     // async fn get_connection_count(&self) -> Result<usize, RpcError>;
 }
 
-// /// Definition of private RPC endpoints that require authentication.
+/// Definition of private RPC endpoints that require authentication.
+#[async_trait::async_trait]
+pub trait ProtectedRpcFunctions {
+    #[doc = include_str!("./documentation/private_endpoints/connect.md")]
+    async fn connect(&self, peer_ips: Vec<SocketAddr>) -> Result<bool, RpcError>;
+
+    #[doc = include_str!("./documentation/private_endpoints/disconnect.md")]
+    async fn disconnect(&self, peer_ip: SocketAddr) -> Result<bool, RpcError>;
+
+    #[doc = include_str!("./documentation/private_endpoints/banpeer.md")]
+    async fn ban_peer(&self, peer_ip: SocketAddr, duration_in_secs: Option<u64>) -> Result<i64, RpcError>;
+
+    #[doc = include_str!("./documentation/private_endpoints/unbanpeer.md")]
+    async fn unban_peer(&self, peer_ip: SocketAddr) -> Result<bool, RpcError>;
+
+    #[doc = include_str!("./documentation/private_endpoints/listbanned.md")]
+    async fn list_banned(&self) -> Result<Vec<serde_json::Value>, RpcError>;
+
+    #[doc = include_str!("./documentation/private_endpoints/startmining.md")]
+    async fn start_mining(&self) -> Result<bool, RpcError>;
+
+    #[doc = include_str!("./documentation/private_endpoints/stopmining.md")]
+    async fn stop_mining(&self) -> Result<bool, RpcError>;
+
+    #[doc = include_str!("./documentation/private_endpoints/setminingrecipient.md")]
+    async fn set_mining_recipient(&self, recipient: String) -> Result<bool, RpcError>;
+
+    #[doc = include_str!("./documentation/private_endpoints/setminingthreads.md")]
+    async fn set_mining_threads(&self, number_of_threads: usize) -> Result<bool, RpcError>;
+}
+
+// TODO: Migrate the remaining private endpoints into `ProtectedRpcFunctions`.
 // #[async_trait::async_trait]
 // pub trait ProtectedRpcFunctions {
 //     #[doc = include_str!("../documentation/private_endpoints/createaccount.md")]
@@ -123,10 +178,4 @@ pub trait RpcFunctions<N: Network> {
 //
 //     #[doc = include_str!("../documentation/private_endpoints/decryptrecord.md")]
 //     async fn decrypt_record(&self, decryption_input: DecryptRecordInput) -> Result<String, RpcError>;
-//
-//     #[doc = include_str!("../documentation/private_endpoints/disconnect.md")]
-//     async fn disconnect(&self, address: SocketAddr);
-//
-//     #[doc = include_str!("../documentation/private_endpoints/connect.md")]
-//     async fn connect(&self, addresses: Vec<SocketAddr>);
 // }